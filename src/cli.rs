@@ -1,8 +1,30 @@
 //! CLI argument definitions
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
+/// Which side wins a token-ID conflict during `Merge`
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum MergePreference {
+    Source,
+    Target,
+}
+
+/// Override for a tokenizer's affixing/byte-level convention, for sync
+/// operations when auto-detection from the model config/pre-tokenizer isn't
+/// reliable
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SchemeOverride {
+    /// Detect from the tokenizer's own model config and pre-tokenizer
+    Auto,
+    /// WordPiece-style: `##` continuing-subword prefix, no suffix
+    Wordpiece,
+    /// GPT-2-style byte-level BPE (`Ġ`-for-space, full byte remap)
+    ByteLevel,
+    /// No affix markers (plain subword pieces)
+    Plain,
+}
+
 #[derive(Parser, Debug)]
 #[command(
     author,
@@ -52,6 +74,16 @@ pub enum Commands {
         /// JSON file with tokens that should never be removed
         #[arg(long)]
         whitelist: Option<PathBuf>,
+
+        /// Save the revision history log to a JSON file, for later `Undo`
+        #[arg(long)]
+        save_history: Option<PathBuf>,
+
+        /// Refuse to save if the final vocab size would exceed this budget
+        /// (ignored when `--keep-size` already caps the vocab at its
+        /// current size)
+        #[arg(long)]
+        max_vocab: Option<usize>,
     },
 
     /// Remove tokens from a JSON file
@@ -67,6 +99,10 @@ pub enum Commands {
         /// Output tokenizer.json file
         #[arg(short, long)]
         output: PathBuf,
+
+        /// Save the revision history log to a JSON file, for later `Undo`
+        #[arg(long)]
+        save_history: Option<PathBuf>,
     },
 
     /// Show tokenizer stats
@@ -74,6 +110,12 @@ pub enum Commands {
         /// Input tokenizer.json file
         #[arg(short, long)]
         input: PathBuf,
+
+        /// Text corpus file, one sample per line: when given, also reports
+        /// the percentage of corpus tokens that encode without falling
+        /// back to `unk_token`
+        #[arg(long)]
+        corpus: Option<PathBuf>,
     },
 
     /// Shrink vocab by removing N longest non-special tokens with highest IDs
@@ -101,6 +143,18 @@ pub enum Commands {
         /// Save removed tokens to a JSON file
         #[arg(long)]
         save_removed: Option<PathBuf>,
+
+        /// Refuse to save if this corpus file becomes unencodable after the
+        /// shrink (see the `Coverage` command)
+        #[arg(long)]
+        require_coverage: Option<PathBuf>,
+
+        /// Text corpus file, one sample per line: when given, victims are
+        /// ranked by actual corpus usage (least-frequent first) instead of
+        /// the plain length/ID heuristic, and the corpus's total token
+        /// count before/after is reported to show the compression cost
+        #[arg(long)]
+        corpus: Option<PathBuf>,
     },
 
     /// Sync single-letter tokens from source to target tokenizer
@@ -128,6 +182,112 @@ pub enum Commands {
         /// Save sync report to a JSON file
         #[arg(long)]
         save_report: Option<PathBuf>,
+
+        /// Text corpus file, one sample per line: when given, tokens
+        /// evicted to make room are ranked by actual corpus usage
+        /// (least-frequent first) instead of the plain length/ID heuristic
+        #[arg(long)]
+        corpus: Option<PathBuf>,
+
+        /// Override the source tokenizer's affixing/byte-level convention
+        /// instead of auto-detecting it from its model config
+        #[arg(long, value_enum, default_value = "auto")]
+        source_scheme: SchemeOverride,
+
+        /// Override the target tokenizer's affixing/byte-level convention
+        /// instead of auto-detecting it from its model config
+        #[arg(long, value_enum, default_value = "auto")]
+        target_scheme: SchemeOverride,
+
+        /// Refuse to save if the final vocab size would exceed this budget
+        #[arg(long)]
+        max_vocab: Option<usize>,
+    },
+
+    /// Reassign a token's content while preserving its numeric ID (e.g.
+    /// repurposing a reserved `<extra_id_0>`-style placeholder slot)
+    Assign {
+        /// Input tokenizer.json file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output tokenizer.json file
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Existing token content to rename (single rename)
+        #[arg(long)]
+        old: Option<String>,
+
+        /// New content for the token (single rename)
+        #[arg(long)]
+        new: Option<String>,
+
+        /// JSON file mapping old token content -> new token content, for
+        /// batch renames applied atomically (nothing is saved if any
+        /// rename in the mapping fails)
+        #[arg(long)]
+        mapping: Option<PathBuf>,
+    },
+
+    /// Union two tokenizers into one consistent model: combined vocab,
+    /// reconciled merges, dense ID reindex
+    Merge {
+        /// Source tokenizer.json file (tokens/merges to pull in)
+        #[arg(short, long)]
+        source: PathBuf,
+
+        /// Target tokenizer.json file (primary; its merge rank order wins)
+        #[arg(short, long)]
+        target: PathBuf,
+
+        /// Output tokenizer.json file
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Which side wins for tokens mapped to different IDs in each
+        #[arg(long, value_enum, default_value = "target")]
+        prefer: MergePreference,
+    },
+
+    /// Check whether a text corpus is fully encodable against a tokenizer's
+    /// vocab, reporting any characters that can't be produced
+    Coverage {
+        /// Input tokenizer.json file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Text corpus file, one sample per line
+        #[arg(short, long)]
+        corpus: PathBuf,
+    },
+
+    /// Learn new merges from a text corpus, growing the vocab toward a
+    /// target size (or for a fixed number of merges)
+    Train {
+        /// Input tokenizer.json file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output tokenizer.json file
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Text corpus file, one sample per line
+        #[arg(short, long)]
+        corpus: PathBuf,
+
+        /// Target vocab size to train up to
+        #[arg(long, default_value = "50000")]
+        vocab_size: usize,
+
+        /// Stop after this many new merges, even if vocab-size isn't reached
+        #[arg(long)]
+        num_merges: Option<usize>,
+
+        /// Minimum pair frequency required to add a merge
+        #[arg(long, default_value = "2")]
+        min_frequency: u32,
     },
 
     /// Sync 2 and 3 letter tokens from source to target tokenizer (with their merges)
@@ -163,5 +323,84 @@ pub enum Commands {
         /// Save sync report to a JSON file
         #[arg(long)]
         save_report: Option<PathBuf>,
+
+        /// Text corpus file, one sample per line: when given, tokens
+        /// evicted to make room are ranked by actual corpus usage
+        /// (least-frequent first) instead of the plain length/ID heuristic
+        #[arg(long)]
+        corpus: Option<PathBuf>,
+
+        /// Override the source tokenizer's affixing/byte-level convention
+        /// instead of auto-detecting it from its model config
+        #[arg(long, value_enum, default_value = "auto")]
+        source_scheme: SchemeOverride,
+
+        /// Override the target tokenizer's affixing/byte-level convention
+        /// instead of auto-detecting it from its model config
+        #[arg(long, value_enum, default_value = "auto")]
+        target_scheme: SchemeOverride,
+
+        /// Refuse to save if the final vocab size would exceed this budget
+        #[arg(long)]
+        max_vocab: Option<usize>,
+    },
+
+    /// Undo the most recent revision(s) from a saved history log (see
+    /// `--save-history` on `Add`/`Remove`, `--save-removed` on `Shrink`, or
+    /// `--save-report` on `SyncChars`/`SyncShortTokens`)
+    Undo {
+        /// Input tokenizer.json file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output tokenizer.json file
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Revision history JSON log to undo against; rewritten in place so
+        /// the next `Undo`/`Redo` can continue from where this left off
+        #[arg(long)]
+        history: PathBuf,
+
+        /// Number of revisions to undo
+        #[arg(long, default_value = "1")]
+        steps: usize,
+    },
+
+    /// Redo the most recently undone revision(s) from a saved history log
+    Redo {
+        /// Input tokenizer.json file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output tokenizer.json file
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Revision history JSON log to redo against; rewritten in place so
+        /// the next `Undo`/`Redo` can continue from where this left off
+        #[arg(long)]
+        history: PathBuf,
+
+        /// Number of revisions to redo
+        #[arg(long, default_value = "1")]
+        steps: usize,
+    },
+
+    /// Structural diff between two tokenizer.json files: added/removed/
+    /// remapped tokens and added/removed merges, via a Merkle-style hash
+    /// tree bucketed by token-id range so unchanged regions are skipped
+    Diff {
+        /// First tokenizer.json file
+        #[arg(short, long)]
+        a: PathBuf,
+
+        /// Second tokenizer.json file
+        #[arg(short, long)]
+        b: PathBuf,
+
+        /// Save the full JSON diff report to a file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 }
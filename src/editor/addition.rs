@@ -2,19 +2,150 @@
 
 use std::collections::HashSet;
 
+use anyhow::{bail, Result};
+
 use crate::tokenizer::Merge;
-use crate::types::AdditionResult;
+use crate::types::{AdditionResult, ReassignResult};
 
 use super::core::BPETokenizerEditor;
 
 impl BPETokenizerEditor {
+    /// Re-assign a token's surface form while preserving its numeric ID.
+    ///
+    /// Useful for repurposing reserved/placeholder slots (e.g. turning
+    /// `<extra_id_0>` into a real domain token) without disturbing the ID
+    /// space that downstream model embeddings depend on. Every `Merge` that
+    /// uses `old` as its left or right input has that operand rewritten to
+    /// `new`. Merges whose computed `result()` is `old` are reported in
+    /// `merges_touched` but keep their inputs unchanged, and the vocab
+    /// entries produced by the rewritten merges are not renamed, so those
+    /// merges' results are not guaranteed to be in vocab afterwards. Fails
+    /// (without mutating anything)
+    /// if `old` is missing, `new` already exists, or rewriting a merge would
+    /// make its result collide with an unrelated existing vocab entry.
+    pub fn reassign_token(&mut self, old: &str, new: &str) -> Result<ReassignResult> {
+        if old == new {
+            bail!("'{}' is already the current token content", old);
+        }
+
+        let id = *self
+            .tokenizer
+            .model
+            .vocab
+            .get(old)
+            .ok_or_else(|| anyhow::anyhow!("Token '{}' does not exist", old))?;
+
+        if self.has_token(new) {
+            bail!("Token '{}' already exists at a different ID", new);
+        }
+
+        // Pre-flight: find affected merges and reject collisions before mutating anything.
+        let mut affected_indices = vec![];
+        for (i, m) in self.tokenizer.model.merges.iter().enumerate() {
+            let old_result = self.merge_result(&m.0, &m.1);
+            if m.0 != old && m.1 != old && old_result != old {
+                continue;
+            }
+
+            let new_left = if m.0 == old { new } else { m.0.as_str() };
+            let new_right = if m.1 == old { new } else { m.1.as_str() };
+            let new_result = self.merge_result(new_left, new_right);
+
+            if new_result != old_result && self.has_token(&new_result) {
+                bail!(
+                    "Cannot reassign '{}' to '{}': merge ({}, {}) would produce '{}', \
+                     which already exists as an unrelated vocab entry",
+                    old,
+                    new,
+                    new_left,
+                    new_right,
+                    new_result
+                );
+            }
+
+            affected_indices.push(i);
+        }
+
+        // Commit: swap the vocab key, keeping the same ID.
+        self.tokenizer.model.vocab.remove(old);
+        self.insert_vocab_entry(new.to_string(), id);
+
+        let mut merges_touched = vec![];
+        for &i in &affected_indices {
+            let m = &mut self.tokenizer.model.merges[i];
+            if m.0 == old {
+                m.0 = new.to_string();
+            }
+            if m.1 == old {
+                m.1 = new.to_string();
+            }
+            merges_touched.push((m.0.clone(), m.1.clone()));
+        }
+
+        self.rebuild_indices();
+
+        Ok(ReassignResult {
+            old_token: old.to_string(),
+            new_token: new.to_string(),
+            id,
+            merges_touched,
+        })
+    }
+
+    /// Batch version of `reassign_token`: validates that every `old` token
+    /// exists and every `new` token is free (accounting for renames within
+    /// the same batch vacating their old slot) before applying any of them,
+    /// then applies them in order. If any rename fails - including one onto
+    /// a token that only a later entry of the batch vacates - the editor is
+    /// restored to its state before the call, so a single bad rename fails
+    /// the whole batch instead of leaving it half-applied.
+    pub fn reassign_tokens(
+        &mut self,
+        renames: &[(String, String)],
+    ) -> Result<Vec<ReassignResult>> {
+        let olds: HashSet<&str> = renames.iter().map(|(old, _)| old.as_str()).collect();
+
+        let mut news_seen: HashSet<&str> = HashSet::new();
+        for (old, new) in renames {
+            if !self.has_token(old) {
+                bail!("Token '{}' does not exist", old);
+            }
+            if self.has_token(new) && !olds.contains(new.as_str()) {
+                bail!("Token '{}' already exists at a different ID", new);
+            }
+            if !news_seen.insert(new.as_str()) {
+                bail!(
+                    "Token '{}' is the rename target of more than one entry in this batch",
+                    new
+                );
+            }
+        }
+
+        let snapshot = self.clone();
+        let results: Result<Vec<ReassignResult>> = renames
+            .iter()
+            .map(|(old, new)| self.reassign_token(old, new))
+            .collect();
+        if results.is_err() {
+            *self = snapshot;
+        }
+        results
+    }
+
     /// Add a token atomically (no merges) - for special tokens and single chars
     pub fn add_token_atomic(&mut self, token: &str) -> bool {
         if self.has_token(token) {
             return false;
         }
         let id = self.get_next_id();
-        self.tokenizer.model.vocab.insert(token.to_string(), id);
+        self.insert_vocab_entry(token.to_string(), id);
+        self.record_revision(
+            format!("add_token_atomic('{}')", token),
+            vec![(token.to_string(), id)],
+            vec![],
+            vec![],
+            vec![],
+        );
         true
     }
 
@@ -29,6 +160,16 @@ impl BPETokenizerEditor {
             };
         }
 
+        if self.is_special_token(token) {
+            self.add_token_atomic(token);
+            return AdditionResult {
+                token: token.to_string(),
+                added: true,
+                method: "special_token".to_string(),
+                added_merges: vec![],
+            };
+        }
+
         let chars: Vec<char> = token.chars().collect();
         if chars.len() == 1 {
             self.add_token_atomic(token);
@@ -40,6 +181,11 @@ impl BPETokenizerEditor {
             };
         }
 
+        // Snapshot vocab before mutating: the helper paths below mint
+        // intermediate chain tokens directly, so the set of new (token, id)
+        // pairs is easiest to recover as a before/after diff.
+        let before: HashSet<String> = self.tokenizer.model.vocab.keys().cloned().collect();
+
         // Find longest prefix in vocab
         let mut prefix: Option<String> = None;
         for i in (1..=token.len()).rev() {
@@ -50,24 +196,45 @@ impl BPETokenizerEditor {
             }
         }
 
-        let added_merges = if let Some(ref pref) = prefix {
+        let (added_merges, realized) = if let Some(ref pref) = prefix {
             let suffix = &token[pref.len()..];
             if suffix.is_empty() {
-                vec![]
+                (vec![], pref.clone())
             } else {
                 self.build_suffix_and_merge(pref, suffix)
             }
         } else {
-            self.build_char_chain(token)
+            self.build_char_chain_marked(token, true, true)
         };
 
-        if !self.has_token(token) {
+        // In affix-marked vocabularies the realized leaf/merge form (e.g.
+        // ending in `end_of_word_suffix`) differs from the bare surface
+        // form the caller asked for; `build_char_chain_marked` already
+        // inserted that realized form, so there is nothing left to add.
+        if realized == token && !self.has_token(token) {
             let id = self.get_next_id();
-            self.tokenizer.model.vocab.insert(token.to_string(), id);
+            self.insert_vocab_entry(token.to_string(), id);
         }
 
         self.rebuild_indices();
 
+        let tokens_added: Vec<(String, u32)> = self
+            .tokenizer
+            .model
+            .vocab
+            .iter()
+            .filter(|(t, _)| !before.contains(*t))
+            .map(|(t, &id)| (t.clone(), id))
+            .collect();
+
+        self.record_revision(
+            format!("add_token_with_merges('{}')", token),
+            tokens_added,
+            added_merges.clone(),
+            vec![],
+            vec![],
+        );
+
         AdditionResult {
             token: token.to_string(),
             added: true,
@@ -81,30 +248,48 @@ impl BPETokenizerEditor {
         }
     }
 
-    /// Build a char chain for a token: a+b -> ab, ab+c -> abc, ...
-    pub(crate) fn build_char_chain(&mut self, token: &str) -> Vec<(String, String)> {
-        let chars: Vec<char> = token.chars().collect();
+    /// Build a char chain for `substring`: a+b -> ab, ab+c -> abc, ...,
+    /// returning the merges added plus the realized form of the final,
+    /// fully-merged symbol.
+    ///
+    /// Each character's own vocab entry carries the model's affix markers
+    /// the way a real BPE vocab would store it: the very first character of
+    /// the token is unmarked, every other character is prefixed with
+    /// `continuing_subword_prefix` (e.g. `##`) since it only ever occurs
+    /// mid-word, and - when `is_end_of_token` is set - the last character is
+    /// additionally suffixed with `end_of_word_suffix`. `is_start_of_token`
+    /// is false when `substring` is the tail of a longer token whose head
+    /// already matched an existing vocab entry, so its first character
+    /// still counts as a continuation.
+    pub(crate) fn build_char_chain_marked(
+        &mut self,
+        substring: &str,
+        is_start_of_token: bool,
+        is_end_of_token: bool,
+    ) -> (Vec<(String, String)>, String) {
+        let chars: Vec<char> = substring.chars().collect();
         if chars.is_empty() {
-            return vec![];
+            return (vec![], String::new());
         }
+        let last_idx = chars.len() - 1;
 
         let mut added_merges = vec![];
-        let mut current = chars[0].to_string();
+        let mut current = self.affixed_symbol(chars[0], !is_start_of_token, is_end_of_token && last_idx == 0);
 
         if !self.has_token(&current) {
             let id = self.get_next_id();
-            self.tokenizer.model.vocab.insert(current.clone(), id);
+            self.insert_vocab_entry(current.clone(), id);
         }
 
-        for ch in chars.iter().skip(1) {
-            let ch_str = ch.to_string();
+        for (i, ch) in chars.iter().enumerate().skip(1) {
+            let ch_str = self.affixed_symbol(*ch, true, is_end_of_token && i == last_idx);
 
             if !self.has_token(&ch_str) {
                 let id = self.get_next_id();
-                self.tokenizer.model.vocab.insert(ch_str.clone(), id);
+                self.insert_vocab_entry(ch_str.clone(), id);
             }
 
-            let new_token = format!("{}{}", current, ch_str);
+            let new_token = self.merge_result(&current, &ch_str);
             let merge_exists = self
                 .tokenizer
                 .model
@@ -122,40 +307,71 @@ impl BPETokenizerEditor {
 
             if !self.has_token(&new_token) {
                 let id = self.get_next_id();
-                self.tokenizer.model.vocab.insert(new_token.clone(), id);
+                self.insert_vocab_entry(new_token.clone(), id);
             }
 
             current = new_token;
         }
 
-        added_merges
+        (added_merges, current)
     }
 
-    /// Build suffix via char chain, then add merge (prefix, suffix) -> token
-    fn build_suffix_and_merge(&mut self, prefix: &str, suffix: &str) -> Vec<(String, String)> {
-        let mut added_merges = vec![];
-
-        if !self.has_token(suffix) {
-            let suffix_merges = self.build_char_chain(suffix);
-            added_merges.extend(suffix_merges);
+    /// Apply `continuing_subword_prefix` (when `continuation` is set) and
+    /// `end_of_word_suffix` (when `end_of_word` is set) to a single
+    /// character, the way affix-marked vocabularies store mid-word and
+    /// word-final symbols.
+    fn affixed_symbol(&self, ch: char, continuation: bool, end_of_word: bool) -> String {
+        let mut s = ch.to_string();
+        if continuation {
+            if let Some(prefix) = self.tokenizer.model.continuing_subword_prefix.as_deref() {
+                if !prefix.is_empty() {
+                    s = format!("{}{}", prefix, s);
+                }
+            }
         }
+        if end_of_word {
+            if let Some(suffix) = self.tokenizer.model.end_of_word_suffix.as_deref() {
+                if !suffix.is_empty() {
+                    s = format!("{}{}", s, suffix);
+                }
+            }
+        }
+        s
+    }
 
+    /// Build the tail (`suffix`) via a continuation-marked char chain, then
+    /// add the merge `(prefix, realized_suffix) -> token`. Returns the
+    /// merges added plus the final realized token form.
+    fn build_suffix_and_merge(
+        &mut self,
+        prefix: &str,
+        suffix: &str,
+    ) -> (Vec<(String, String)>, String) {
+        let (mut added_merges, realized_suffix) =
+            self.build_char_chain_marked(suffix, false, true);
+
+        let new_token = self.merge_result(prefix, &realized_suffix);
         let merge_exists = self
             .tokenizer
             .model
             .merges
             .iter()
-            .any(|m| m.0 == prefix && m.1 == suffix);
+            .any(|m| m.0 == prefix && m.1 == realized_suffix);
 
         if !merge_exists {
-            added_merges.push((prefix.to_string(), suffix.to_string()));
+            added_merges.push((prefix.to_string(), realized_suffix.clone()));
             self.tokenizer
                 .model
                 .merges
-                .push(Merge(prefix.to_string(), suffix.to_string()));
+                .push(Merge(prefix.to_string(), realized_suffix));
         }
 
-        added_merges
+        if !self.has_token(&new_token) {
+            let id = self.get_next_id();
+            self.insert_vocab_entry(new_token.clone(), id);
+        }
+
+        (added_merges, new_token)
     }
 
     /// Add a merge if it doesn't exist
@@ -189,3 +405,47 @@ impl BPETokenizerEditor {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_support::create_test_tokenizer;
+
+    #[test]
+    fn test_reassign_tokens_rejects_duplicate_new_targets_without_mutating() {
+        // Regression test: both pairs validate individually against the
+        // original vocab, but collapsing "a" and "b" onto the same new
+        // token "x" must be rejected before either rename is applied.
+        let mut editor = BPETokenizerEditor::new(create_test_tokenizer(
+            vec![("a", 0), ("b", 1)],
+            vec![],
+        ));
+
+        let result = editor.reassign_tokens(&[
+            ("a".to_string(), "x".to_string()),
+            ("b".to_string(), "x".to_string()),
+        ]);
+
+        assert!(result.is_err());
+        assert!(editor.has_token("a"));
+        assert!(editor.has_token("b"));
+        assert!(!editor.has_token("x"));
+    }
+
+    #[test]
+    fn test_reassign_tokens_applies_independent_renames() {
+        let mut editor = BPETokenizerEditor::new(create_test_tokenizer(
+            vec![("a", 0), ("b", 1)],
+            vec![],
+        ));
+
+        let result = editor.reassign_tokens(&[
+            ("a".to_string(), "c".to_string()),
+            ("b".to_string(), "d".to_string()),
+        ]);
+
+        assert!(result.is_ok());
+        assert!(editor.has_token("c"));
+        assert!(editor.has_token("d"));
+    }
+}
@@ -0,0 +1,153 @@
+//! Vocab-size budget guard
+//!
+//! Lets callers building fixed-footprint models set a hard ceiling on vocab
+//! size and discover overflow before `save()`, instead of only after.
+
+use anyhow::{bail, Result};
+
+use crate::types::AdditionResult;
+
+use super::core::BPETokenizerEditor;
+
+impl BPETokenizerEditor {
+    /// Set the maximum vocab size enforced by `add_token_with_merges_budgeted`.
+    /// Pass `None` to remove the ceiling.
+    pub fn set_max_vocab_size(&mut self, max: Option<usize>) {
+        self.max_vocab_size = max;
+    }
+
+    /// Number of vocab slots left before `max_vocab_size` is hit, or `None`
+    /// if no budget is configured.
+    pub fn remaining_capacity(&self) -> Option<usize> {
+        self.max_vocab_size
+            .map(|max| max.saturating_sub(self.vocab_size()))
+    }
+
+    /// Add a token with merges, consulting the configured vocab-size budget.
+    ///
+    /// In strict mode, refuses to grow past `max_vocab_size` and returns an
+    /// error reporting how many slots remain, leaving `self` exactly as it
+    /// was before the call. In report mode (`strict = false`), the addition
+    /// proceeds and the current/max/remaining figures are emitted alongside
+    /// the progress output already printed during batch adds.
+    ///
+    /// A single call can mint more than one vocab slot - `add_token_with_merges`
+    /// may chain-mint intermediate chars/subwords via `build_char_chain_marked`
+    /// - so the check that matters is the vocab size *after* the add, not just
+    /// whether capacity was already at zero going in. Since the exact number
+    /// of slots an add will mint depends on which intermediate chars/subwords
+    /// are already in vocab, strict mode snapshots `self` first and restores
+    /// it if the add turns out to have overflowed the budget, rather than
+    /// trying to predict the mint count up front.
+    pub fn add_token_with_merges_budgeted(
+        &mut self,
+        token: &str,
+        strict: bool,
+    ) -> Result<AdditionResult> {
+        if self.has_token(token) {
+            return Ok(self.add_token_with_merges(token));
+        }
+
+        if let (Some(max), Some(remaining)) = (self.max_vocab_size, self.remaining_capacity()) {
+            if remaining == 0 {
+                if strict {
+                    bail!(
+                        "Vocab budget exhausted: {}/{} slots used, 0 remaining (cannot add '{}')",
+                        self.vocab_size(),
+                        max,
+                        token
+                    );
+                }
+                eprintln!(
+                    "   [budget] current={} max={} remaining=0 (adding '{}' overflows the budget)",
+                    self.vocab_size(),
+                    max,
+                    token
+                );
+            }
+        }
+
+        let before = if strict { Some(self.clone()) } else { None };
+        let size_before = self.vocab_size();
+        let result = self.add_token_with_merges(token);
+
+        if let Some(max) = self.max_vocab_size {
+            let size = self.vocab_size();
+            let slots_minted = size.saturating_sub(size_before);
+            if size > max {
+                if strict {
+                    if let Some(before) = before {
+                        *self = before;
+                    }
+                    bail!(
+                        "Vocab budget exceeded: adding '{}' would mint {} vocab slot(s), growing vocab to {}/{} ({} over budget); left unchanged",
+                        token,
+                        slots_minted,
+                        size,
+                        max,
+                        size - max
+                    );
+                }
+                eprintln!(
+                    "   [budget] current={} max={} over_budget={} (adding '{}' minted {} vocab slot(s))",
+                    size,
+                    max,
+                    size - max,
+                    token,
+                    slots_minted
+                );
+            } else if !strict {
+                eprintln!(
+                    "   [budget] current={} max={} remaining={}",
+                    size,
+                    max,
+                    max - size
+                );
+            }
+        }
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_support::create_test_tokenizer;
+    use crate::tokenizer::Tokenizer;
+
+    fn tokenizer_with(vocab: Vec<(&str, u32)>) -> Tokenizer {
+        create_test_tokenizer(vocab, vec![])
+    }
+
+    #[test]
+    fn test_budgeted_add_rejects_multi_slot_overflow_without_mutating() {
+        // Regression test: one slot remains, but "bc" chain-mints 3 new
+        // vocab entries ("b", "c", "bc") since neither char pre-exists.
+        // Strict mode must refuse the add and leave the vocab untouched,
+        // not bail only after already growing past max_vocab_size.
+        let mut editor = BPETokenizerEditor::new(tokenizer_with(vec![("a", 0)]));
+        editor.set_max_vocab_size(Some(2));
+
+        let result = editor.add_token_with_merges_budgeted("bc", true);
+
+        assert!(result.is_err());
+        assert_eq!(editor.vocab_size(), 1);
+        assert!(!editor.has_token("b"));
+        assert!(!editor.has_token("c"));
+        assert!(!editor.has_token("bc"));
+        assert!(editor.tokenizer.model.merges.is_empty());
+    }
+
+    #[test]
+    fn test_budgeted_add_report_mode_allows_overflow() {
+        let mut editor = BPETokenizerEditor::new(tokenizer_with(vec![("a", 0)]));
+        editor.set_max_vocab_size(Some(2));
+
+        let result = editor.add_token_with_merges_budgeted("bc", false);
+
+        assert!(result.is_ok());
+        assert!(editor.has_token("bc"));
+        assert!(editor.vocab_size() > 2);
+    }
+}
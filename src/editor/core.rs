@@ -7,7 +7,11 @@ use std::path::PathBuf;
 
 use crate::tokenizer::Tokenizer;
 
+use super::history::Revision;
+use super::special_tokens::SpecialTokens;
+
 /// BPE Tokenizer Editor with consistency guarantees
+#[derive(Clone)]
 pub struct BPETokenizerEditor {
     pub tokenizer: Tokenizer,
     // Indices for fast lookup
@@ -15,6 +19,30 @@ pub struct BPETokenizerEditor {
     pub(crate) uses: HashMap<String, HashSet<usize>>, // token -> merge indices where used as input
     pub(crate) used_ids: HashSet<u32>,
     pub(crate) next_id: u32,
+    /// Registry of tokens that should never be treated as ordinary vocab
+    /// entries by the vocab-size heuristics.
+    pub special_tokens: SpecialTokens,
+    /// Optional external usage counts (e.g. from tokenizing a corpus), used
+    /// to prefer evicting genuinely unused tokens during pruning. Empty
+    /// means no usage data is known and pruning falls back to the
+    /// length/produced heuristic.
+    pub usage_counts: HashMap<String, u64>,
+    /// Optional hard ceiling on vocab size, enforced by the budgeted add
+    /// path. `None` means unbounded.
+    pub max_vocab_size: Option<usize>,
+    /// Reverse id -> token index, mirroring `tokenizer.model.vocab`, for
+    /// O(1) lookups. Kept in sync by `rebuild_indices` (full resync after
+    /// any mutation) and directly by `insert_vocab_entry`/`remove_vocab_entry`.
+    pub(crate) index2token: HashMap<u32, String>,
+    /// Session id this editor's revisions are scoped under (see
+    /// `history::RevisionId`).
+    pub(crate) session_id: u64,
+    /// Next revision counter to hand out within `session_id`.
+    pub(crate) rev_counter: u64,
+    /// Revisions applied so far, oldest first.
+    pub(crate) history: Vec<Revision>,
+    /// Revisions popped off by `undo` that `redo` can still replay.
+    pub(crate) redo_stack: Vec<Revision>,
 }
 
 impl BPETokenizerEditor {
@@ -22,6 +50,7 @@ impl BPETokenizerEditor {
     pub fn new(tokenizer: Tokenizer) -> Self {
         let used_ids: HashSet<u32> = tokenizer.model.vocab.values().copied().collect();
         let next_id = used_ids.iter().max().copied().unwrap_or(0) + 1;
+        let special_tokens = SpecialTokens::from_tokenizer(&tokenizer);
 
         let mut editor = Self {
             tokenizer,
@@ -29,11 +58,47 @@ impl BPETokenizerEditor {
             uses: HashMap::new(),
             used_ids,
             next_id,
+            special_tokens,
+            usage_counts: HashMap::new(),
+            max_vocab_size: None,
+            index2token: HashMap::new(),
+            session_id: Self::new_session_id(),
+            rev_counter: 0,
+            history: Vec::new(),
+            redo_stack: Vec::new(),
         };
         editor.rebuild_indices();
         editor
     }
 
+    /// Set the usage-count map consulted by pruning heuristics (e.g. built
+    /// from tokenizing a corpus). Pass an empty map to clear it and revert
+    /// to the plain length/produced heuristic.
+    pub fn set_usage_counts(&mut self, usage_counts: HashMap<String, u64>) {
+        self.usage_counts = usage_counts;
+    }
+
+    /// The configured unknown-token string, or `None` if unset.
+    pub fn unk_token(&self) -> Option<&str> {
+        if self.tokenizer.model.unk_token.is_empty() {
+            None
+        } else {
+            Some(&self.tokenizer.model.unk_token)
+        }
+    }
+
+    /// Set (or clear, with `None`) the unknown-token string emitted by
+    /// `encode` for symbols missing from vocab.
+    pub fn set_unk_token(&mut self, token: Option<&str>) {
+        self.tokenizer.model.unk_token = token.unwrap_or_default().to_string();
+    }
+
+    /// Set whether consecutive unk emissions collapse into a single unk
+    /// token during `encode`.
+    pub fn set_fuse_unk(&mut self, fuse_unk: bool) {
+        self.tokenizer.model.fuse_unk = fuse_unk;
+    }
+
     /// Load a tokenizer from a JSON file
     pub fn load(path: &PathBuf) -> Result<Self> {
         let content =
@@ -60,6 +125,7 @@ impl BPETokenizerEditor {
 
         let mut json_value = serde_json::to_value(&self.tokenizer)?;
         json_value["model"]["vocab"] = serde_json::Value::Object(vocab_ordered);
+        json_value["added_tokens"] = serde_json::to_value(self.build_added_tokens())?;
 
         let content = serde_json::to_string_pretty(&json_value)
             .with_context(|| "Failed to serialize tokenizer")?;
@@ -67,17 +133,53 @@ impl BPETokenizerEditor {
         Ok(())
     }
 
+    /// Materialize `added_tokens` entries for every reserved special token
+    /// that doesn't already have one, alongside whatever entries were
+    /// already present, sorted by ID.
+    fn build_added_tokens(&self) -> Vec<serde_json::Value> {
+        let mut entries = self.tokenizer.added_tokens.clone();
+        let existing: HashSet<String> = entries
+            .iter()
+            .filter_map(|e| e.get("content").and_then(|v| v.as_str()))
+            .map(|s| s.to_string())
+            .collect();
+
+        for (token, &id) in &self.special_tokens.reserved {
+            if existing.contains(token) {
+                continue;
+            }
+            entries.push(serde_json::json!({
+                "id": id,
+                "content": token,
+                "single_word": false,
+                "lstrip": false,
+                "rstrip": false,
+                "normalized": false,
+                "special": true,
+            }));
+        }
+
+        entries.sort_by_key(|e| e.get("id").and_then(|v| v.as_u64()).unwrap_or(0));
+        entries
+    }
+
     /// Rebuild internal indices for fast lookups
     pub fn rebuild_indices(&mut self) {
         self.producer.clear();
         self.uses.clear();
 
+        let prefix_cfg = self.tokenizer.model.continuing_subword_prefix.clone();
         for (i, merge) in self.tokenizer.model.merges.iter().enumerate() {
-            let prod = merge.result();
+            let prod = compute_merge_result(prefix_cfg.as_deref(), &merge.0, &merge.1);
             self.producer.entry(prod).or_insert(i);
             self.uses.entry(merge.0.clone()).or_default().insert(i);
             self.uses.entry(merge.1.clone()).or_default().insert(i);
         }
+
+        self.index2token.clear();
+        for (token, &id) in &self.tokenizer.model.vocab {
+            self.index2token.insert(id, token.clone());
+        }
     }
 
     /// Get the current vocab size
@@ -95,6 +197,9 @@ impl BPETokenizerEditor {
         self.tokenizer.model.vocab.contains_key(token)
     }
 
+    /// Reserve the next free ID. The reverse `index2token` entry for it only
+    /// appears once the caller inserts the corresponding vocab entry, via
+    /// `insert_vocab_entry` or the next `rebuild_indices` resync.
     pub(crate) fn get_next_id(&mut self) -> u32 {
         while self.used_ids.contains(&self.next_id) {
             self.next_id += 1;
@@ -105,7 +210,61 @@ impl BPETokenizerEditor {
         id
     }
 
+    /// Free an ID for reuse. Callers that also hold the released token
+    /// string should use `remove_vocab_entry` instead, which keeps
+    /// `index2token` in sync directly.
     pub(crate) fn release_id(&mut self, id: u32) {
         self.used_ids.remove(&id);
     }
+
+    /// Insert a vocab entry, keeping `index2token` in sync immediately.
+    pub(crate) fn insert_vocab_entry(&mut self, token: String, id: u32) {
+        self.index2token.insert(id, token.clone());
+        self.tokenizer.model.vocab.insert(token, id);
+    }
+
+    /// Remove a vocab entry (and release its ID), keeping `index2token` in
+    /// sync immediately. Returns the removed ID, if any.
+    pub(crate) fn remove_vocab_entry(&mut self, token: &str) -> Option<u32> {
+        let id = self.tokenizer.model.vocab.remove(token)?;
+        self.index2token.remove(&id);
+        self.release_id(id);
+        Some(id)
+    }
+
+    /// Look up a token string by its numeric ID in O(1).
+    pub fn token_by_id(&self, id: u32) -> Option<&str> {
+        self.index2token.get(&id).map(|s| s.as_str())
+    }
+
+    /// Compute the token string produced by merging `left` and `right`,
+    /// honoring `continuing_subword_prefix` (e.g. `##`): continuation
+    /// pieces already carry the prefix in vocab, so it is stripped from
+    /// `right` before concatenating to recover the joined surface form.
+    /// Plain BPE models (no configured prefix) concatenate verbatim, same
+    /// as `Merge::result()`.
+    pub(crate) fn merge_result(&self, left: &str, right: &str) -> String {
+        compute_merge_result(
+            self.tokenizer.model.continuing_subword_prefix.as_deref(),
+            left,
+            right,
+        )
+    }
+}
+
+/// Free-standing version of [`BPETokenizerEditor::merge_result`] that takes
+/// the prefix config by value, so it can be used from within loops that
+/// already hold a borrow of `self.tokenizer.model.merges`.
+pub(crate) fn compute_merge_result(
+    continuing_subword_prefix: Option<&str>,
+    left: &str,
+    right: &str,
+) -> String {
+    match continuing_subword_prefix {
+        Some(prefix) if !prefix.is_empty() => {
+            let right_stripped = right.strip_prefix(prefix).unwrap_or(right);
+            format!("{}{}", left, right_stripped)
+        }
+        _ => format!("{}{}", left, right),
+    }
 }
@@ -0,0 +1,165 @@
+//! Encodability / coverage checking against the edited vocab+merges
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::types::{CoverageReport, CoverageResult, EncodeCoverageResult};
+
+use super::core::BPETokenizerEditor;
+
+impl BPETokenizerEditor {
+    /// Build a frequency-ranked report of how much of `texts` is covered by
+    /// the current vocab, honoring `byte_fallback` the same way
+    /// `check_coverage` does. Unlike `check_coverage`, this is weighted by
+    /// occurrence count rather than just distinct characters, so the
+    /// out-of-vocabulary list reflects what actually shows up most in real
+    /// text.
+    pub fn coverage_report(&self, texts: &[String]) -> CoverageReport {
+        let mut char_freq: HashMap<char, u64> = HashMap::new();
+        let mut total_chars: u64 = 0;
+        for line in texts {
+            for ch in line.chars() {
+                total_chars += 1;
+                *char_freq.entry(ch).or_insert(0) += 1;
+            }
+        }
+
+        let mut covered_chars: u64 = 0;
+        let mut oov: Vec<(char, u64)> = Vec::new();
+        for (&ch, &freq) in &char_freq {
+            let covered = self.has_token(&ch.to_string())
+                || (self.tokenizer.model.byte_fallback && self.char_covered_by_byte_fallback(ch));
+            if covered {
+                covered_chars += freq;
+            } else {
+                oov.push((ch, freq));
+            }
+        }
+        oov.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        CoverageReport {
+            total_chars: total_chars as usize,
+            covered_chars: covered_chars as usize,
+            unk_count: oov.iter().map(|(_, freq)| *freq).sum::<u64>() as usize,
+            top_oov: oov,
+        }
+    }
+
+    /// Check whether every character appearing in `corpus` can be encoded
+    /// against the current vocab. Honors `byte_fallback`: when enabled, a
+    /// character with no direct char token still counts as covered if all
+    /// of its UTF-8 bytes exist as byte tokens (`<0xXX>`); when disabled, a
+    /// missing char is a hard gap.
+    pub fn check_coverage(&self, corpus: impl Iterator<Item = String>) -> CoverageResult {
+        let mut seen: BTreeSet<char> = BTreeSet::new();
+        for line in corpus {
+            seen.extend(line.chars());
+        }
+
+        let mut byte_fallback_chars = Vec::new();
+        let mut missing_chars = Vec::new();
+
+        for &ch in &seen {
+            if self.has_token(&ch.to_string()) {
+                continue;
+            }
+            if self.tokenizer.model.byte_fallback && self.char_covered_by_byte_fallback(ch) {
+                byte_fallback_chars.push(ch);
+            } else {
+                missing_chars.push(ch);
+            }
+        }
+
+        CoverageResult {
+            chars_checked: seen.len(),
+            fully_covered: missing_chars.is_empty(),
+            byte_fallback_chars,
+            missing_chars,
+        }
+    }
+
+    /// Encode every line of `texts` through the real BPE loop
+    /// (`encode_to_tokens`) and measure the fraction of resulting tokens
+    /// that aren't `unk_token`, so users can see whether a shrink or sync
+    /// pushed actual tokenization coverage below an acceptable threshold -
+    /// unlike `check_coverage`, which only checks character presence and
+    /// can't see unk fallback caused by a missing merge path.
+    pub fn encode_coverage(&self, texts: &[String]) -> EncodeCoverageResult {
+        let unk = &self.tokenizer.model.unk_token;
+        let mut tokens_checked: u64 = 0;
+        let mut unk_tokens: u64 = 0;
+
+        for line in texts {
+            for token in self.encode_to_tokens(line) {
+                tokens_checked += 1;
+                if !unk.is_empty() && &token == unk {
+                    unk_tokens += 1;
+                }
+            }
+        }
+
+        let covered_pct = if tokens_checked == 0 {
+            100.0
+        } else {
+            100.0 * (tokens_checked - unk_tokens) as f64 / tokens_checked as f64
+        };
+
+        EncodeCoverageResult {
+            tokens_checked,
+            unk_tokens,
+            covered_pct,
+        }
+    }
+
+    /// Check that every UTF-8 byte of `ch` exists in vocab as a
+    /// `<0xXX>`-style byte-fallback token.
+    fn char_covered_by_byte_fallback(&self, ch: char) -> bool {
+        let mut buf = [0u8; 4];
+        ch.encode_utf8(&mut buf)
+            .as_bytes()
+            .iter()
+            .all(|b| self.has_token(&format!("<0x{:02X}>", b)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_support::create_test_tokenizer;
+    use crate::tokenizer::Tokenizer;
+
+    fn tokenizer_with(vocab: Vec<(&str, u32)>) -> Tokenizer {
+        create_test_tokenizer(vocab, vec![])
+    }
+
+    #[test]
+    fn test_encode_coverage_full_on_wordpiece_vocab() {
+        // Regression test: before initial_symbols became affix-aware, this
+        // WordPiece word would encode as all-unk and covered_pct would read
+        // 0% instead of 100%.
+        let mut tokenizer =
+            tokenizer_with(vec![("w", 0), ("##o", 1), ("##r", 2), ("##d", 3)]);
+        tokenizer.model.continuing_subword_prefix = Some("##".to_string());
+        let editor = BPETokenizerEditor::new(tokenizer);
+
+        let result = editor.encode_coverage(&["word".to_string()]);
+
+        assert_eq!(result.tokens_checked, 4);
+        assert_eq!(result.unk_tokens, 0);
+        assert_eq!(result.covered_pct, 100.0);
+    }
+
+    #[test]
+    fn test_encode_coverage_reports_unk_for_byte_level_vocab_gap() {
+        let mut tokenizer = tokenizer_with(vec![("c", 0), ("a", 1)]);
+        tokenizer.pre_tokenizer = Some(serde_json::json!({"type": "ByteLevel"}));
+        let editor = BPETokenizerEditor::new(tokenizer);
+
+        // "café" byte-level-encodes to 5 symbols; only the plain-ASCII "c"
+        // and "a" are in vocab, so the rest fall back to unk.
+        let result = editor.encode_coverage(&["café".to_string()]);
+
+        assert_eq!(result.tokens_checked, 5);
+        assert_eq!(result.unk_tokens, 3);
+        assert!(result.covered_pct < 100.0);
+    }
+}
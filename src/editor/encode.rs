@@ -0,0 +1,358 @@
+//! Greedy BPE encoding, so an edit can be verified by actually tokenizing
+//! text with it rather than just inspecting vocab/merges.
+
+use std::collections::HashMap;
+
+use super::core::BPETokenizerEditor;
+use super::scheme::mark_word_chars;
+
+/// Minimal splitmix64-style PRNG, used only to make merge dropout
+/// reproducible across calls with the same seed (no external RNG
+/// dependency is pulled in just for this).
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_f64(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl BPETokenizerEditor {
+    /// Tokenize `text` into the numeric IDs the edited vocab+merges would
+    /// produce for it. `dropout` overrides `model.dropout` for this call
+    /// only (`Some(p)` applies BPE-dropout at rate `p` regardless of the
+    /// model's configured value, `None` falls back to it); the dropout RNG
+    /// is seeded from 0, so repeated calls with the same input and dropout
+    /// are reproducible.
+    pub fn encode(&self, text: &str, dropout: Option<f32>) -> Vec<u32> {
+        self.encode_to_tokens_with_dropout(text, dropout, 0)
+            .into_iter()
+            .filter_map(|t| self.tokenizer.model.vocab.get(&t).copied())
+            .collect()
+    }
+
+    /// Concatenate each ID's vocab string via `token_by_id`. IDs with no
+    /// corresponding token are skipped. Affix markers (e.g. `##`) and the
+    /// byte-level alphabet are not undone, so the result is the text in the
+    /// vocab's own form.
+    pub fn decode(&self, ids: &[u32]) -> String {
+        ids.iter()
+            .filter_map(|&id| self.token_by_id(id))
+            .collect()
+    }
+
+    /// Tokenize `text` into the token strings produced by the standard BPE
+    /// inner loop: start from characters (expanded to byte tokens when
+    /// `byte_fallback` is set and the char itself isn't in vocab, else
+    /// falling back to `unk_token`), then repeatedly merge the adjacent
+    /// pair with the lowest rank in `model.merges` until no pair has one.
+    pub fn encode_to_tokens(&self, text: &str) -> Vec<String> {
+        self.encode_to_tokens_with_seed(text, 0)
+    }
+
+    /// Same as `encode_to_tokens`, but honors `model.dropout` (if set) by
+    /// randomly skipping a candidate merge with that probability each time
+    /// it is considered, seeded by `seed` so results are reproducible.
+    pub fn encode_to_tokens_with_seed(&self, text: &str, seed: u64) -> Vec<String> {
+        self.encode_to_tokens_with_dropout(text, None, seed)
+    }
+
+    /// Same as `encode_to_tokens_with_seed`, but `dropout` overrides
+    /// `model.dropout` for this call (`None` falls back to it).
+    pub fn encode_to_tokens_with_dropout(
+        &self,
+        text: &str,
+        dropout: Option<f32>,
+        seed: u64,
+    ) -> Vec<String> {
+        let rank: HashMap<(&str, &str), usize> = self
+            .tokenizer
+            .model
+            .merges
+            .iter()
+            .enumerate()
+            .map(|(i, m)| ((m.0.as_str(), m.1.as_str()), i))
+            .collect();
+
+        let dropout = dropout
+            .map(|d| d as f64)
+            .unwrap_or_else(|| self.tokenizer.model.dropout.unwrap_or(0.0));
+        let mut rng = SplitMix64(seed ^ 0x2545_F491_4F6C_DD1D);
+
+        let mut tokens = Vec::new();
+        for word in text.split_whitespace() {
+            let mut symbols = self.initial_symbols(word);
+
+            loop {
+                let mut best: Option<(usize, usize)> = None; // (rank, position)
+                for i in 0..symbols.len().saturating_sub(1) {
+                    let Some(&r) = rank.get(&(symbols[i].as_str(), symbols[i + 1].as_str()))
+                    else {
+                        continue;
+                    };
+                    if dropout > 0.0 && rng.next_f64() < dropout {
+                        continue;
+                    }
+                    let is_better = match best {
+                        Some((best_rank, _)) => r < best_rank,
+                        None => true,
+                    };
+                    if is_better {
+                        best = Some((r, i));
+                    }
+                }
+
+                let Some((_, pos)) = best else { break };
+                let merged = self.merge_result(&symbols[pos], &symbols[pos + 1]);
+                symbols[pos] = merged;
+                symbols.remove(pos + 1);
+            }
+
+            for sym in symbols {
+                if self.has_token(&sym) {
+                    tokens.push(sym);
+                } else {
+                    tokens.push(self.tokenizer.model.unk_token.clone());
+                }
+            }
+        }
+
+        if self.tokenizer.model.fuse_unk && !self.tokenizer.model.unk_token.is_empty() {
+            let unk = &self.tokenizer.model.unk_token;
+            let mut fused = Vec::with_capacity(tokens.len());
+            for tok in tokens {
+                if &tok == unk && fused.last().map(|t| t == unk).unwrap_or(false) {
+                    continue;
+                }
+                fused.push(tok);
+            }
+            tokens = fused;
+        }
+
+        tokens
+    }
+
+    /// Encode every line of `texts` and tally how often each resulting
+    /// token actually appears, alongside the corpus's total token count.
+    /// Unlike `coverage_report` (which counts characters), this runs the
+    /// real merge loop via `encode_to_tokens`, so the counts reflect what
+    /// the current vocab+merges would actually produce - the basis for
+    /// ranking shrink/sync candidates by genuine corpus usage rather than
+    /// the plain length/ID heuristic.
+    pub fn corpus_usage_counts(&self, texts: &[String]) -> (HashMap<String, u64>, u64) {
+        let mut counts: HashMap<String, u64> = HashMap::new();
+        let mut total: u64 = 0;
+
+        for line in texts {
+            for token in self.encode_to_tokens(line) {
+                total += 1;
+                *counts.entry(token).or_insert(0) += 1;
+            }
+        }
+
+        (counts, total)
+    }
+
+    /// Build the initial, unmerged symbol sequence for a single word, marked
+    /// the same way `add_token_with_merges`/`build_char_chain_marked` mint
+    /// vocab entries: per `self.affix_scheme()`, not a hardcoded `▁`
+    /// boundary marker (see `mark_word_chars`). Any non-byte-level character
+    /// missing from vocab is expanded to its UTF-8 byte tokens when
+    /// `byte_fallback` is enabled; byte-level schemes already route every
+    /// byte through the GPT-2 alphabet, so there's no separate fallback atop
+    /// that.
+    fn initial_symbols(&self, word: &str) -> Vec<String> {
+        let scheme = self.affix_scheme();
+        let marked = mark_word_chars(&scheme, word);
+
+        if scheme.byte_level {
+            return marked;
+        }
+
+        let mut symbols = Vec::with_capacity(marked.len());
+        for (ch, sym) in word.chars().zip(marked) {
+            if self.has_token(&sym) {
+                symbols.push(sym);
+                continue;
+            }
+
+            if self.tokenizer.model.byte_fallback {
+                let mut buf = [0u8; 4];
+                for b in ch.encode_utf8(&mut buf).as_bytes() {
+                    symbols.push(format!("<0x{:02X}>", b));
+                }
+            } else {
+                symbols.push(sym);
+            }
+        }
+
+        symbols
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_support::create_test_tokenizer;
+
+    #[test]
+    fn test_encode_to_tokens_applies_merges_in_rank_order() {
+        // "b"+"c" is rank 0 (applied first), so it wins over "a"+"b" even
+        // though the latter would also be a valid adjacent pair initially.
+        let tokenizer = create_test_tokenizer(
+            vec![("a", 0), ("b", 1), ("c", 2), ("bc", 3), ("ab", 4)],
+            vec![("b", "c"), ("a", "b")],
+        );
+        let editor = BPETokenizerEditor::new(tokenizer);
+
+        assert_eq!(editor.encode_to_tokens("abc"), vec!["a".to_string(), "bc".to_string()]);
+    }
+
+    #[test]
+    fn test_encode_falls_back_to_unk_for_unknown_chars() {
+        let tokenizer = create_test_tokenizer(vec![("a", 0)], vec![]);
+        let editor = BPETokenizerEditor::new(tokenizer);
+
+        assert_eq!(
+            editor.encode_to_tokens("az"),
+            vec!["a".to_string(), "<unk>".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_fuse_unk_collapses_consecutive_unk_tokens() {
+        let mut tokenizer = create_test_tokenizer(vec![], vec![]);
+        tokenizer.model.fuse_unk = true;
+        let editor = BPETokenizerEditor::new(tokenizer);
+
+        // Neither 'x' nor 'y' is in vocab, so both encode to <unk> and should
+        // fuse into a single <unk>.
+        assert_eq!(editor.encode_to_tokens("xy"), vec!["<unk>".to_string()]);
+    }
+
+    #[test]
+    fn test_decode_concatenates_tokens_by_id() {
+        let tokenizer = create_test_tokenizer(vec![("a", 0), ("b", 1), ("c", 2)], vec![]);
+        let editor = BPETokenizerEditor::new(tokenizer);
+
+        assert_eq!(editor.decode(&[0, 1, 2]), "abc");
+    }
+
+    #[test]
+    fn test_full_dropout_disables_all_merges() {
+        let tokenizer = create_test_tokenizer(
+            vec![("a", 0), ("b", 1), ("ab", 2)],
+            vec![("a", "b")],
+        );
+        let editor = BPETokenizerEditor::new(tokenizer);
+
+        // dropout = 1.0 means every candidate merge is always skipped.
+        let tokens = editor.encode_to_tokens_with_dropout("ab", Some(1.0), 42);
+        assert_eq!(tokens, vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn test_dropout_is_reproducible_for_same_seed() {
+        let tokenizer = create_test_tokenizer(
+            vec![("a", 0), ("b", 1), ("c", 2), ("ab", 3), ("abc", 4)],
+            vec![("a", "b"), ("ab", "c")],
+        );
+        let mut tokenizer2 = create_test_tokenizer(
+            vec![("a", 0), ("b", 1), ("c", 2), ("ab", 3), ("abc", 4)],
+            vec![("a", "b"), ("ab", "c")],
+        );
+        tokenizer2.model.dropout = None;
+        let editor = BPETokenizerEditor::new(tokenizer);
+        let editor2 = BPETokenizerEditor::new(tokenizer2);
+
+        let first = editor.encode_to_tokens_with_dropout("abc", Some(0.5), 7);
+        let second = editor2.encode_to_tokens_with_dropout("abc", Some(0.5), 7);
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_encode_wordpiece_scheme_marks_continuation_chars_without_merge() {
+        // No merge for ("w", "##o"), so the word surfaces as its raw
+        // per-char WordPiece marking rather than degrading to unk soup.
+        let mut tokenizer = create_test_tokenizer(
+            vec![("w", 0), ("##o", 1), ("##r", 2), ("##d", 3)],
+            vec![],
+        );
+        tokenizer.model.continuing_subword_prefix = Some("##".to_string());
+        let editor = BPETokenizerEditor::new(tokenizer);
+
+        assert_eq!(
+            editor.encode_to_tokens("word"),
+            vec!["w".to_string(), "##o".to_string(), "##r".to_string(), "##d".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_encode_wordpiece_scheme_applies_merge_and_round_trips_through_decode() {
+        let mut tokenizer = create_test_tokenizer(
+            vec![("w", 0), ("##o", 1), ("wo", 2)],
+            vec![("w", "##o")],
+        );
+        tokenizer.model.continuing_subword_prefix = Some("##".to_string());
+        let editor = BPETokenizerEditor::new(tokenizer);
+
+        let tokens = editor.encode_to_tokens("wo");
+        assert_eq!(tokens, vec!["wo".to_string()]);
+
+        let ids = editor.encode("wo", None);
+        assert_eq!(editor.decode(&ids), "wo");
+    }
+
+    #[test]
+    fn test_encode_byte_level_scheme_decodes_to_byte_level_form() {
+        use super::super::scheme::encode_byte_level;
+
+        // "café" has a multi-byte UTF-8 char ('é'), so this actually
+        // exercises the GPT-2 byte-level remap rather than just identity
+        // mapping plain ASCII.
+        let encoded = encode_byte_level("café");
+        let chars: Vec<String> = encoded.chars().map(|c| c.to_string()).collect();
+        let vocab: Vec<(&str, u32)> = chars
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (s.as_str(), i as u32))
+            .collect();
+        let mut tokenizer = create_test_tokenizer(vocab, vec![]);
+        tokenizer.pre_tokenizer = Some(serde_json::json!({"type": "ByteLevel"}));
+        let editor = BPETokenizerEditor::new(tokenizer);
+
+        let tokens = editor.encode_to_tokens("café");
+        assert!(tokens.iter().all(|t| t != &editor.tokenizer.model.unk_token));
+        assert_eq!(tokens.concat(), encoded);
+
+        let ids = editor.encode("café", None);
+        assert_eq!(editor.decode(&ids), encoded);
+    }
+
+    #[test]
+    fn test_corpus_usage_counts_tallies_wordpiece_tokens_not_unk() {
+        // Regression test for corpus_usage_counts riding on a WordPiece-aware
+        // initial_symbols: before that fix, every word here would encode as
+        // all-unk soup instead of the real "w"/"##o"/"##r"/"##d" tokens.
+        let mut tokenizer = create_test_tokenizer(
+            vec![("w", 0), ("##o", 1), ("##r", 2), ("##d", 3)],
+            vec![],
+        );
+        tokenizer.model.continuing_subword_prefix = Some("##".to_string());
+        let editor = BPETokenizerEditor::new(tokenizer);
+
+        let (counts, total) = editor.corpus_usage_counts(&["word word".to_string()]);
+
+        assert_eq!(total, 8);
+        assert_eq!(counts.get("w"), Some(&2));
+        assert_eq!(counts.get("##o"), Some(&2));
+        assert_eq!(counts.get("##r"), Some(&2));
+        assert_eq!(counts.get("##d"), Some(&2));
+        assert!(!counts.contains_key(&editor.tokenizer.model.unk_token));
+    }
+}
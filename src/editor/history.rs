@@ -0,0 +1,280 @@
+//! Reversible edit history: mutating vocab/merge operations record a
+//! `Revision` capturing the diff they produced, modeled loosely on
+//! xi-editor's `RevId`/engine. `undo`/`redo` replay a revision's diff
+//! directly against the in-memory editor; the CLI layer persists a
+//! `HistoryLog` as JSON (via `--save-history`/`--save-report`/`--save-removed`) so a later,
+//! separate `Undo`/`Redo` invocation can replay it against a saved
+//! tokenizer, since each CLI command loads fresh from disk.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::tokenizer::Merge;
+
+use super::core::BPETokenizerEditor;
+
+/// A revision's identity: a monotonically increasing counter scoped to a
+/// session id derived from the editor's creation time and PID, so revision logs
+/// produced by different processes (or machines) never collide when their
+/// entries are compared or merged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevisionId {
+    pub session_id: u64,
+    pub counter: u64,
+}
+
+/// One reversible edit: the vocab/merge diff a mutating call produced.
+/// Undoing a revision removes `tokens_added`/`merges_added` and re-inserts
+/// `tokens_removed`/`merges_removed` (at their original IDs); redoing it
+/// does the opposite.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Revision {
+    pub id: RevisionId,
+    pub label: String,
+    pub tokens_added: Vec<(String, u32)>,
+    pub merges_added: Vec<(String, String)>,
+    pub tokens_removed: Vec<(String, u32)>,
+    pub merges_removed: Vec<(String, String)>,
+}
+
+/// A serializable undo/redo log: revisions already applied (`done`, oldest
+/// first) and revisions popped off by `undo` that `redo` can still replay
+/// (`undone`, oldest-undone-first). What `--save-history`/`--save-report`/
+/// `--save-removed` write and `Undo`/`Redo` read back.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryLog {
+    pub done: Vec<Revision>,
+    pub undone: Vec<Revision>,
+}
+
+impl BPETokenizerEditor {
+    pub(crate) fn new_session_id() -> u64 {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        let pid = std::process::id() as u64;
+        nanos ^ pid.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+    }
+
+    /// Record a revision for a completed mutation and clear the redo stack,
+    /// since a fresh edit invalidates whatever was undone before it. A
+    /// no-op diff (e.g. adding a token that already existed) records
+    /// nothing.
+    pub(crate) fn record_revision(
+        &mut self,
+        label: impl Into<String>,
+        tokens_added: Vec<(String, u32)>,
+        merges_added: Vec<(String, String)>,
+        tokens_removed: Vec<(String, u32)>,
+        merges_removed: Vec<(String, String)>,
+    ) {
+        if tokens_added.is_empty()
+            && merges_added.is_empty()
+            && tokens_removed.is_empty()
+            && merges_removed.is_empty()
+        {
+            return;
+        }
+
+        self.rev_counter += 1;
+        self.history.push(Revision {
+            id: RevisionId {
+                session_id: self.session_id,
+                counter: self.rev_counter,
+            },
+            label: label.into(),
+            tokens_added,
+            merges_added,
+            tokens_removed,
+            merges_removed,
+        });
+        self.redo_stack.clear();
+    }
+
+    /// The revisions recorded so far this session, oldest first.
+    pub fn history(&self) -> &[Revision] {
+        &self.history
+    }
+
+    /// Snapshot the undo/redo stacks for persistence (e.g. to JSON via
+    /// `--save-history`/`--save-report`/`--save-removed`).
+    pub fn export_history(&self) -> HistoryLog {
+        HistoryLog {
+            done: self.history.clone(),
+            undone: self.redo_stack.clone(),
+        }
+    }
+
+    /// Load previously-saved undo/redo stacks, e.g. at the start of a CLI
+    /// `Undo`/`Redo` invocation that reloaded the tokenizer fresh from disk
+    /// and needs its revision log back to act on.
+    pub fn load_history(&mut self, log: HistoryLog) {
+        self.history = log.done;
+        self.redo_stack = log.undone;
+    }
+
+    /// Undo the most recent revision, returning its label. Errs (without
+    /// mutating anything) if there is nothing to undo.
+    pub fn undo(&mut self) -> Result<String> {
+        let rev = self.history.pop().ok_or_else(|| anyhow!("Nothing to undo"))?;
+        self.apply_inverse(&rev);
+        let label = rev.label.clone();
+        self.redo_stack.push(rev);
+        Ok(label)
+    }
+
+    /// Redo the most recently undone revision, returning its label. Errs
+    /// (without mutating anything) if there is nothing to redo.
+    pub fn redo(&mut self) -> Result<String> {
+        let rev = self
+            .redo_stack
+            .pop()
+            .ok_or_else(|| anyhow!("Nothing to redo"))?;
+        self.apply_forward(&rev);
+        let label = rev.label.clone();
+        self.history.push(rev);
+        Ok(label)
+    }
+
+    fn apply_inverse(&mut self, rev: &Revision) {
+        self.remove_exact(&rev.tokens_added, &rev.merges_added);
+        self.add_exact(&rev.tokens_removed, &rev.merges_removed);
+    }
+
+    fn apply_forward(&mut self, rev: &Revision) {
+        self.remove_exact(&rev.tokens_removed, &rev.merges_removed);
+        self.add_exact(&rev.tokens_added, &rev.merges_added);
+    }
+
+    /// Remove exactly these vocab entries and merges (matched by value, not
+    /// recomputed via `remove_token_and_dependencies`'s cascade - a
+    /// revision already recorded the full cascade it caused).
+    fn remove_exact(&mut self, tokens: &[(String, u32)], merges: &[(String, String)]) {
+        for (token, _) in tokens {
+            self.remove_vocab_entry(token);
+        }
+        for (a, b) in merges {
+            if let Some(pos) = self
+                .tokenizer
+                .model
+                .merges
+                .iter()
+                .position(|m| &m.0 == a && &m.1 == b)
+            {
+                self.tokenizer.model.merges.remove(pos);
+            }
+        }
+        self.rebuild_indices();
+    }
+
+    /// Re-insert exactly these vocab entries at their original IDs and
+    /// merges, reserving the IDs so they can't be handed out again by
+    /// `get_next_id`.
+    fn add_exact(&mut self, tokens: &[(String, u32)], merges: &[(String, String)]) {
+        for (token, id) in tokens {
+            if self.has_token(token) {
+                continue;
+            }
+            self.used_ids.insert(*id);
+            self.insert_vocab_entry(token.clone(), *id);
+            if *id >= self.next_id {
+                self.next_id = *id + 1;
+            }
+        }
+        for (a, b) in merges {
+            let exists = self
+                .tokenizer
+                .model
+                .merges
+                .iter()
+                .any(|m| &m.0 == a && &m.1 == b);
+            if !exists {
+                self.tokenizer.model.merges.push(Merge(a.clone(), b.clone()));
+            }
+        }
+        self.rebuild_indices();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_support::create_test_tokenizer;
+
+    #[test]
+    fn test_undo_removal_restores_token_and_id() {
+        let tokenizer = create_test_tokenizer(
+            vec![("a", 0), ("b", 1), ("ab", 2)],
+            vec![("a", "b")],
+        );
+        let mut editor = BPETokenizerEditor::new(tokenizer);
+
+        editor.remove_token_and_dependencies("ab");
+        assert!(!editor.has_token("ab"));
+        assert_eq!(editor.merges_count(), 0);
+
+        let label = editor.undo().unwrap();
+        assert!(label.contains("ab"));
+        assert_eq!(editor.tokenizer.model.vocab.get("ab"), Some(&2));
+        assert_eq!(editor.merges_count(), 1);
+    }
+
+    #[test]
+    fn test_redo_reapplies_removal() {
+        let tokenizer = create_test_tokenizer(vec![("a", 0), ("b", 1)], vec![]);
+        let mut editor = BPETokenizerEditor::new(tokenizer);
+
+        editor.remove_token_and_dependencies("b");
+        editor.undo().unwrap();
+        assert!(editor.has_token("b"));
+
+        editor.redo().unwrap();
+        assert!(!editor.has_token("b"));
+    }
+
+    #[test]
+    fn test_undo_addition_removes_minted_token() {
+        let tokenizer = create_test_tokenizer(vec![("a", 0)], vec![]);
+        let mut editor = BPETokenizerEditor::new(tokenizer);
+
+        assert!(editor.add_token_atomic("x"));
+        assert!(editor.has_token("x"));
+
+        editor.undo().unwrap();
+        assert!(!editor.has_token("x"));
+    }
+
+    #[test]
+    fn test_new_edit_clears_redo_stack() {
+        let tokenizer = create_test_tokenizer(vec![("a", 0), ("b", 1)], vec![]);
+        let mut editor = BPETokenizerEditor::new(tokenizer);
+
+        editor.remove_token_and_dependencies("a");
+        editor.undo().unwrap();
+        assert_eq!(editor.redo_stack.len(), 1);
+
+        editor.remove_token_and_dependencies("b");
+        assert_eq!(editor.redo_stack.len(), 0);
+        assert!(editor.redo().is_err());
+    }
+
+    #[test]
+    fn test_history_log_round_trips_through_json() {
+        let tokenizer = create_test_tokenizer(vec![("a", 0), ("b", 1)], vec![]);
+        let mut editor = BPETokenizerEditor::new(tokenizer);
+        editor.remove_token_and_dependencies("a");
+
+        let log = editor.export_history();
+        let json = serde_json::to_string(&log).unwrap();
+        let restored: HistoryLog = serde_json::from_str(&json).unwrap();
+
+        let tokenizer2 = create_test_tokenizer(vec![("b", 1)], vec![]);
+        let mut editor2 = BPETokenizerEditor::new(tokenizer2);
+        editor2.load_history(restored);
+        editor2.undo().unwrap();
+        assert!(editor2.has_token("a"));
+    }
+}
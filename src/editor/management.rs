@@ -1,8 +1,8 @@
 //! Vocab size management methods
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use crate::types::{BatchAddResult, ShrinkResult, TokenRemovalInfo};
+use crate::types::{BatchAddResult, IdConsistencyReport, ShrinkResult, TokenRemovalInfo};
 
 use super::core::BPETokenizerEditor;
 
@@ -21,39 +21,78 @@ impl BPETokenizerEditor {
         // Protect space marker
         protected.insert("▁".to_string());
 
-        // Protect special tokens
-        for tok in self.tokenizer.model.vocab.keys() {
-            if (tok.starts_with('<') && tok.ends_with('>'))
-                || (tok.starts_with('[') && tok.ends_with(']'))
-            {
-                protected.insert(tok.clone());
+        // Protect tokens registered in the special-token registry
+        protected.extend(self.special_tokens.tokens().iter().cloned());
+
+        // Protect bare single-character continuation/suffix forms (e.g. a
+        // WordPiece-style "##a") so shrinking can't strand a word-internal
+        // or word-final piece that merges still depend on.
+        if let Some(prefix) = &self.tokenizer.model.continuing_subword_prefix {
+            if !prefix.is_empty() {
+                for tok in self.tokenizer.model.vocab.keys() {
+                    if let Some(rest) = tok.strip_prefix(prefix.as_str()) {
+                        if rest.chars().count() == 1 {
+                            protected.insert(tok.clone());
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(suffix) = &self.tokenizer.model.end_of_word_suffix {
+            if !suffix.is_empty() {
+                for tok in self.tokenizer.model.vocab.keys() {
+                    if let Some(rest) = tok.strip_suffix(suffix.as_str()) {
+                        if rest.chars().count() == 1 {
+                            protected.insert(tok.clone());
+                        }
+                    }
+                }
             }
         }
 
         protected
     }
 
-    /// Select a token to remove based on heuristics
+    /// Select a token to remove based on heuristics.
+    ///
+    /// When `usage_counts` has been populated, candidates with a known
+    /// usage count are scored by `count * len` and the lowest-utility one
+    /// is preferred; candidates with no known count are only considered
+    /// once every known-usage candidate is exhausted, ranked by the plain
+    /// length/produced heuristic.
     pub fn select_token_to_remove(&self, protected: &HashSet<String>) -> Option<(String, String)> {
-        let mut candidates: Vec<(usize, bool, &String)> = vec![];
+        let mut known: Vec<(u64, &String)> = vec![];
+        let mut unknown: Vec<(usize, bool, &String)> = vec![];
 
         for tok in self.tokenizer.model.vocab.keys() {
-            if protected.contains(tok) {
+            if protected.contains(tok) || self.is_special_token(tok) {
                 continue;
             }
             let len = tok.chars().count();
-            let is_produced = self.producer.contains_key(tok);
-            candidates.push((len, is_produced, tok));
+            match self.usage_counts.get(tok) {
+                Some(&count) => known.push((count.saturating_mul(len as u64), tok)),
+                None => {
+                    let is_produced = self.producer.contains_key(tok);
+                    unknown.push((len, is_produced, tok));
+                }
+            }
+        }
+
+        if !known.is_empty() {
+            known.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.cmp(b.1)));
+            let (score, tok) = known[0];
+            let reason = format!("Removed by usage-weighted pruning: count*len={}", score);
+            return Some((tok.clone(), reason));
         }
 
-        if candidates.is_empty() {
+        if unknown.is_empty() {
             return None;
         }
 
         // Sort: longest first, produced first, then alphabetic for stability
-        candidates.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)).then(a.2.cmp(b.2)));
+        unknown.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.cmp(&a.1)).then(a.2.cmp(b.2)));
 
-        let (len, produced, tok) = candidates[0];
+        let (len, produced, tok) = unknown[0];
         let reason = format!(
             "Removed to keep vocab size fixed. len={}, merge_produced={}",
             len, produced
@@ -62,7 +101,10 @@ impl BPETokenizerEditor {
         Some((tok.clone(), reason))
     }
 
-    /// Find N tokens to remove: longest non-special tokens with ID >= min_id
+    /// Find N tokens to remove: by default the longest non-special tokens
+    /// with ID >= min_id; when `usage_counts` is populated, tokens with a
+    /// known low `count * len` product are preferred over the length-only
+    /// ranking so genuinely unused tokens are evicted first.
     pub fn find_tokens_to_shrink(&self, count: usize, min_id: u32) -> Vec<(String, u32, usize)> {
         let mut candidates: Vec<(String, u32, usize)> = vec![];
 
@@ -76,17 +118,28 @@ impl BPETokenizerEditor {
                 continue;
             }
 
-            if (tok.starts_with('<') && tok.ends_with('>'))
-                || (tok.starts_with('[') && tok.ends_with(']'))
-            {
+            if self.is_special_token(tok) {
                 continue;
             }
 
             candidates.push((tok.clone(), id, char_len));
         }
 
-        // Sort by length DESC, then by ID DESC
-        candidates.sort_by(|a, b| b.2.cmp(&a.2).then(b.1.cmp(&a.1)));
+        if self.usage_counts.is_empty() {
+            // Sort by length DESC, then by ID DESC
+            candidates.sort_by(|a, b| b.2.cmp(&a.2).then(b.1.cmp(&a.1)));
+        } else {
+            candidates.sort_by(|a, b| {
+                let score_a = self.usage_counts.get(&a.0).map(|&c| c * a.2 as u64);
+                let score_b = self.usage_counts.get(&b.0).map(|&c| c * b.2 as u64);
+                match (score_a, score_b) {
+                    (Some(sa), Some(sb)) => sa.cmp(&sb).then(b.1.cmp(&a.1)),
+                    (Some(_), None) => std::cmp::Ordering::Less,
+                    (None, Some(_)) => std::cmp::Ordering::Greater,
+                    (None, None) => b.2.cmp(&a.2).then(b.1.cmp(&a.1)),
+                }
+            });
+        }
 
         candidates.into_iter().take(count).collect()
     }
@@ -156,22 +209,50 @@ impl BPETokenizerEditor {
             .collect()
     }
 
-    /// Reassign sequential IDs to vocab
-    pub fn reassign_ids(&mut self) {
+    /// Reassign sequential IDs to vocab, first building an id -> tokens map
+    /// from the vocab to detect and report ID collisions (multiple tokens
+    /// claiming the same ID) and gaps in the ID space before they're closed.
+    /// `index2token` is rebuilt to match the new IDs.
+    pub fn reassign_ids(&mut self) -> IdConsistencyReport {
+        let mut by_id: HashMap<u32, Vec<String>> = HashMap::new();
+        for (tok, &id) in &self.tokenizer.model.vocab {
+            by_id.entry(id).or_default().push(tok.clone());
+        }
+
+        let mut collisions: Vec<(u32, Vec<String>)> = by_id
+            .iter()
+            .filter(|(_, toks)| toks.len() > 1)
+            .map(|(&id, toks)| (id, toks.clone()))
+            .collect();
+        collisions.sort_by_key(|(id, _)| *id);
+
+        let mut gaps = vec![];
+        if let (Some(&min_id), Some(&max_id)) = (by_id.keys().min(), by_id.keys().max()) {
+            for id in min_id..=max_id {
+                if !by_id.contains_key(&id) {
+                    gaps.push(id);
+                }
+            }
+        }
+
         let mut sorted_tokens: Vec<_> = self.tokenizer.model.vocab.keys().cloned().collect();
         sorted_tokens
             .sort_by(|a, b| self.tokenizer.model.vocab[a].cmp(&self.tokenizer.model.vocab[b]));
 
         self.tokenizer.model.vocab.clear();
         self.used_ids.clear();
+        self.index2token.clear();
 
         for (i, tok) in sorted_tokens.into_iter().enumerate() {
             let id = i as u32;
+            self.index2token.insert(id, tok.clone());
             self.tokenizer.model.vocab.insert(tok, id);
             self.used_ids.insert(id);
         }
 
         self.next_id = self.used_ids.len() as u32;
+
+        IdConsistencyReport { collisions, gaps }
     }
 
     /// Add tokens while keeping vocab size fixed
@@ -206,6 +287,16 @@ impl BPETokenizerEditor {
                     result.tokens_added,
                     result.tokens_removed
                 );
+                if let (Some(max), Some(remaining)) =
+                    (self.max_vocab_size, self.remaining_capacity())
+                {
+                    eprintln!(
+                        "   [budget] current={} max={} remaining={}",
+                        self.vocab_size(),
+                        max,
+                        remaining
+                    );
+                }
             }
 
             if self.has_token(token) {
@@ -0,0 +1,211 @@
+//! Combining two tokenizers into one consistent model
+
+use std::collections::HashSet;
+
+use crate::types::MergeResult;
+
+use super::core::{compute_merge_result, BPETokenizerEditor};
+
+/// How to resolve a token whose string is present in both tokenizers under
+/// different numeric IDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeConflictStrategy {
+    /// Keep this editor's (the union target's) existing ID
+    PreferTarget,
+    /// Adopt the other editor's (the union source's) ID
+    PreferSource,
+}
+
+impl BPETokenizerEditor {
+    /// Union `other`'s vocabulary and merges into `self`, treating `self` as
+    /// the primary tokenizer: its merge rank order is preserved, and
+    /// `other`'s merges are appended afterward, skipping any already
+    /// present and dropping any whose operands or result aren't in the
+    /// unioned vocab. Tokens present in both vocabs under different IDs are
+    /// resolved per `strategy`; new tokens from `other` get a fresh ID.
+    /// Finishes with `reindex_vocab` to produce a dense ID space.
+    pub fn merge_with(
+        &mut self,
+        other: &BPETokenizerEditor,
+        strategy: MergeConflictStrategy,
+    ) -> MergeResult {
+        let target_vocab_size = self.vocab_size();
+        let source_vocab_size = other.vocab_size();
+
+        let mut tokens_added_from_source = 0usize;
+        let mut id_conflicts = 0usize;
+
+        for (token, &other_id) in &other.tokenizer.model.vocab {
+            match self.tokenizer.model.vocab.get(token).copied() {
+                Some(existing_id) => {
+                    if existing_id != other_id {
+                        id_conflicts += 1;
+                        if strategy == MergeConflictStrategy::PreferSource {
+                            self.tokenizer.model.vocab.insert(token.clone(), other_id);
+                        }
+                    }
+                }
+                None => {
+                    let id = self.get_next_id();
+                    self.insert_vocab_entry(token.clone(), id);
+                    tokens_added_from_source += 1;
+                }
+            }
+        }
+
+        let existing_merges: HashSet<(String, String)> = self
+            .tokenizer
+            .model
+            .merges
+            .iter()
+            .map(|m| (m.0.clone(), m.1.clone()))
+            .collect();
+        let prefix_cfg = self.tokenizer.model.continuing_subword_prefix.clone();
+
+        let mut merges_added_from_source = 0usize;
+        let mut merges_dropped = 0usize;
+
+        for merge in &other.tokenizer.model.merges {
+            if existing_merges.contains(&(merge.0.clone(), merge.1.clone())) {
+                continue;
+            }
+
+            let result = compute_merge_result(prefix_cfg.as_deref(), &merge.0, &merge.1);
+            if self.has_token(&merge.0) && self.has_token(&merge.1) && self.has_token(&result) {
+                self.tokenizer.model.merges.push(merge.clone());
+                merges_added_from_source += 1;
+            } else {
+                merges_dropped += 1;
+            }
+        }
+
+        self.rebuild_indices();
+        let reindex_result = self.reindex_vocab();
+
+        // Final sweep over the whole unioned merge table (not just the
+        // freshly imported merges): reuses the same cleaning logic `Validate
+        // --dry-run=false` applies, so any merge left dangling by the union
+        // or the reindex is repaired by dropping it rather than shipping a
+        // tokenizer that can't encode.
+        let merges_repaired = self.remove_invalid_merges();
+
+        MergeResult {
+            source_vocab_size,
+            target_vocab_size,
+            tokens_added_from_source,
+            id_conflicts,
+            merges_added_from_source,
+            merges_dropped,
+            merges_repaired,
+            final_vocab_size: self.vocab_size(),
+            final_merges_count: self.merges_count(),
+            ids_remapped: reindex_result.ids_remapped,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_support::create_test_tokenizer;
+
+    #[test]
+    fn test_merge_adds_new_tokens_and_merges_from_source() {
+        let mut target = BPETokenizerEditor::new(create_test_tokenizer(
+            vec![("a", 0), ("b", 1)],
+            vec![],
+        ));
+        let source = BPETokenizerEditor::new(create_test_tokenizer(
+            vec![("a", 0), ("b", 1), ("c", 2), ("bc", 3)],
+            vec![("b", "c")],
+        ));
+
+        let result = target.merge_with(&source, MergeConflictStrategy::PreferTarget);
+
+        assert_eq!(result.tokens_added_from_source, 2); // "c", "bc"
+        assert_eq!(result.merges_added_from_source, 1);
+        assert!(target.has_token("c"));
+        assert!(target.has_token("bc"));
+        assert!(target
+            .get_merge_set()
+            .contains(&("b".to_string(), "c".to_string())));
+    }
+
+    #[test]
+    fn test_merge_drops_merges_whose_operands_are_missing() {
+        let mut target = BPETokenizerEditor::new(create_test_tokenizer(
+            vec![("a", 0), ("b", 1)],
+            vec![],
+        ));
+        // Source has a merge for "c"+"d", but neither operand is a standalone
+        // vocab entry in the source, so nothing brings them into the union.
+        let source = BPETokenizerEditor::new(create_test_tokenizer(
+            vec![("a", 0), ("b", 1), ("cd", 2)],
+            vec![("c", "d")],
+        ));
+
+        let result = target.merge_with(&source, MergeConflictStrategy::PreferTarget);
+
+        assert_eq!(result.merges_dropped, 1);
+        assert_eq!(result.merges_added_from_source, 0);
+        assert!(!target
+            .get_merge_set()
+            .contains(&("c".to_string(), "d".to_string())));
+    }
+
+    #[test]
+    fn test_merge_conflict_prefer_target_keeps_existing_id() {
+        let mut target = BPETokenizerEditor::new(create_test_tokenizer(
+            vec![("a", 0), ("b", 1)],
+            vec![],
+        ));
+        let source = BPETokenizerEditor::new(create_test_tokenizer(
+            vec![("a", 0), ("b", 99)],
+            vec![],
+        ));
+
+        let result = target.merge_with(&source, MergeConflictStrategy::PreferTarget);
+
+        assert_eq!(result.id_conflicts, 1);
+        assert_eq!(target.tokenizer.model.vocab.get("b"), Some(&1));
+    }
+
+    #[test]
+    fn test_merge_conflict_prefer_source_adopts_other_id() {
+        // "b" conflicts: target has it at 1, source at 99. Adopting the
+        // source's (much larger) ID pushes "b" to sort last once
+        // `reindex_vocab` re-derives sequential IDs from current order,
+        // which is how the adoption shows up after the merge completes.
+        let mut target = BPETokenizerEditor::new(create_test_tokenizer(
+            vec![("a", 0), ("b", 1), ("c", 2)],
+            vec![],
+        ));
+        let source = BPETokenizerEditor::new(create_test_tokenizer(
+            vec![("a", 0), ("b", 99)],
+            vec![],
+        ));
+
+        let result = target.merge_with(&source, MergeConflictStrategy::PreferSource);
+
+        assert_eq!(result.id_conflicts, 1);
+        assert_eq!(result.final_vocab_size, 3);
+        assert_eq!(target.tokenizer.model.vocab.get("b"), Some(&2));
+    }
+
+    #[test]
+    fn test_merge_validation_sweep_repairs_dangling_merges() {
+        // Build a target whose own vocab+merges are already inconsistent -
+        // "xy" has no vocab entry of its own - so the post-union validation
+        // sweep has something to repair even with an empty source.
+        let mut target = BPETokenizerEditor::new(create_test_tokenizer(
+            vec![("x", 0), ("y", 1)],
+            vec![("x", "y")],
+        ));
+        let source = BPETokenizerEditor::new(create_test_tokenizer(vec![], vec![]));
+
+        let result = target.merge_with(&source, MergeConflictStrategy::PreferTarget);
+
+        assert_eq!(result.merges_repaired, 1);
+        assert!(target.get_merge_set().is_empty());
+    }
+}
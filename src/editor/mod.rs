@@ -1,11 +1,28 @@
 //! BPE Tokenizer Editor modules
 
 mod addition;
+mod budget;
 mod core;
+mod coverage;
+mod diff;
+mod encode;
+mod history;
 mod management;
+mod merge;
+mod ordering;
 mod reindex;
 mod removal;
+mod scheme;
+mod special_tokens;
 mod sync;
+#[cfg(test)]
+mod test_support;
+mod trainer;
 mod validation;
 
 pub use core::BPETokenizerEditor;
+pub use history::{HistoryLog, Revision, RevisionId};
+pub use merge::MergeConflictStrategy;
+pub use scheme::{translate_token, AffixScheme};
+pub use special_tokens::SpecialTokens;
+pub use trainer::TrainConfig;
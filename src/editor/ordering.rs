@@ -0,0 +1,171 @@
+//! Merge list ordering: HuggingFace BPE treats merge position as priority,
+//! so a merge can only fire if both its inputs are already producible by
+//! earlier merges (or are base single-char tokens with no producer at all).
+
+use std::collections::VecDeque;
+
+use anyhow::{bail, Result};
+
+use crate::types::MergeOrderViolation;
+
+use super::core::BPETokenizerEditor;
+
+impl BPETokenizerEditor {
+    /// Find merges that appear before a merge producing one of their own
+    /// inputs - these would misfire under greedy left-to-right BPE
+    /// application. Single-character inputs have no producer and are never
+    /// flagged.
+    pub fn validate_merge_order(&self) -> Vec<MergeOrderViolation> {
+        let mut violations = vec![];
+
+        for (i, m) in self.tokenizer.model.merges.iter().enumerate() {
+            for input in [&m.0, &m.1] {
+                if let Some(&j) = self.producer.get(input) {
+                    if j > i {
+                        violations.push(MergeOrderViolation {
+                            merge_index: i,
+                            merge: (m.0.clone(), m.1.clone()),
+                            depends_on_index: j,
+                            depends_on_merge: (
+                                self.tokenizer.model.merges[j].0.clone(),
+                                self.tokenizer.model.merges[j].1.clone(),
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+
+        violations
+    }
+
+    /// Reorder `model.merges` into a valid topological order via Kahn's
+    /// algorithm: each merge depends on the merges producing its two
+    /// inputs. Fails if a cycle is detected, which should be impossible for
+    /// well-formed BPE.
+    pub fn topological_sort_merges(&mut self) -> Result<()> {
+        let n = self.tokenizer.model.merges.len();
+
+        let mut dependents: Vec<Vec<usize>> = vec![vec![]; n];
+        let mut indegree: Vec<usize> = vec![0; n];
+
+        for (j, m) in self.tokenizer.model.merges.iter().enumerate() {
+            for input in [&m.0, &m.1] {
+                if let Some(&i) = self.producer.get(input) {
+                    if i != j {
+                        dependents[i].push(j);
+                        indegree[j] += 1;
+                    }
+                }
+            }
+        }
+
+        let mut queue: VecDeque<usize> = (0..n).filter(|&i| indegree[i] == 0).collect();
+        let mut order = Vec::with_capacity(n);
+
+        while let Some(i) = queue.pop_front() {
+            order.push(i);
+            for &j in &dependents[i] {
+                indegree[j] -= 1;
+                if indegree[j] == 0 {
+                    queue.push_back(j);
+                }
+            }
+        }
+
+        if order.len() != n {
+            bail!(
+                "Cannot topologically sort merges: a cycle was detected among {} merges \
+                 (this should be impossible for well-formed BPE)",
+                n - order.len()
+            );
+        }
+
+        let old_merges = std::mem::take(&mut self.tokenizer.model.merges);
+        self.tokenizer.model.merges = order.into_iter().map(|i| old_merges[i].clone()).collect();
+        self.rebuild_indices();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_support::create_test_tokenizer;
+
+    #[test]
+    fn test_validate_merge_order_detects_out_of_order_dependency() {
+        // "ab" + "c" comes before the merge that produces "ab" - invalid.
+        let tokenizer = create_test_tokenizer(
+            vec![("a", 0), ("b", 1), ("c", 2), ("ab", 3), ("abc", 4)],
+            vec![("ab", "c"), ("a", "b")],
+        );
+        let editor = BPETokenizerEditor::new(tokenizer);
+
+        let violations = editor.validate_merge_order();
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].merge_index, 0);
+        assert_eq!(violations[0].depends_on_index, 1);
+    }
+
+    #[test]
+    fn test_validate_merge_order_accepts_well_ordered_merges() {
+        let tokenizer = create_test_tokenizer(
+            vec![("a", 0), ("b", 1), ("c", 2), ("ab", 3), ("abc", 4)],
+            vec![("a", "b"), ("ab", "c")],
+        );
+        let editor = BPETokenizerEditor::new(tokenizer);
+
+        assert!(editor.validate_merge_order().is_empty());
+    }
+
+    #[test]
+    fn test_topological_sort_fixes_out_of_order_merges() {
+        let tokenizer = create_test_tokenizer(
+            vec![("a", 0), ("b", 1), ("c", 2), ("ab", 3), ("abc", 4)],
+            vec![("ab", "c"), ("a", "b")],
+        );
+        let mut editor = BPETokenizerEditor::new(tokenizer);
+
+        assert!(!editor.validate_merge_order().is_empty());
+        editor.topological_sort_merges().unwrap();
+        assert!(editor.validate_merge_order().is_empty());
+
+        // "a"+"b" must now come before "ab"+"c".
+        let pos_ab = editor
+            .tokenizer
+            .model
+            .merges
+            .iter()
+            .position(|m| m.0 == "a" && m.1 == "b")
+            .unwrap();
+        let pos_abc = editor
+            .tokenizer
+            .model
+            .merges
+            .iter()
+            .position(|m| m.0 == "ab" && m.1 == "c")
+            .unwrap();
+        assert!(pos_ab < pos_abc);
+    }
+
+    #[test]
+    fn test_topological_sort_is_noop_on_already_sorted_merges() {
+        let tokenizer = create_test_tokenizer(
+            vec![("a", 0), ("b", 1), ("c", 2), ("ab", 3), ("abc", 4)],
+            vec![("a", "b"), ("ab", "c")],
+        );
+        let mut editor = BPETokenizerEditor::new(tokenizer);
+
+        editor.topological_sort_merges().unwrap();
+        let merges: Vec<(&str, &str)> = editor
+            .tokenizer
+            .model
+            .merges
+            .iter()
+            .map(|m| (m.0.as_str(), m.1.as_str()))
+            .collect();
+        assert_eq!(merges, vec![("a", "b"), ("ab", "c")]);
+    }
+}
@@ -17,7 +17,16 @@ impl BPETokenizerEditor {
     /// # Returns
     ///
     /// A `ReindexResult` containing statistics about the reindexing operation.
+    ///
+    /// Tokens pinned via `reserve_special_token` (or loaded from
+    /// `added_tokens`) keep their ID unconditionally; only the remaining
+    /// tokens are compacted into the ID space left over once reserved IDs
+    /// are excluded.
     pub fn reindex_vocab(&mut self) -> ReindexResult {
+        if !self.special_tokens.reserved.is_empty() {
+            return self.reindex_vocab_with_reserved();
+        }
+
         let vocab = &self.tokenizer.model.vocab;
         let vocab_size = vocab.len();
 
@@ -103,8 +112,10 @@ impl BPETokenizerEditor {
 
         // Update internal state
         self.used_ids.clear();
-        for id in self.tokenizer.model.vocab.values() {
-            self.used_ids.insert(*id);
+        self.index2token.clear();
+        for (token, &id) in &self.tokenizer.model.vocab {
+            self.used_ids.insert(id);
+            self.index2token.insert(id, token.clone());
         }
         self.next_id = vocab_size as u32;
 
@@ -124,6 +135,85 @@ impl BPETokenizerEditor {
         }
     }
 
+    /// Reindex-vocab path used when one or more tokens are pinned via
+    /// `reserve_special_token`: reserved entries are left untouched, and
+    /// every other token is compacted (in current-ID order) into the
+    /// remaining IDs, smallest available first.
+    fn reindex_vocab_with_reserved(&mut self) -> ReindexResult {
+        let vocab_size = self.tokenizer.model.vocab.len();
+        let merges_count = self.tokenizer.model.merges.len();
+
+        if vocab_size == 0 {
+            return ReindexResult {
+                vocab_size: 0,
+                merges_count,
+                old_min_id: 0,
+                old_max_id: 0,
+                new_min_id: 0,
+                new_max_id: 0,
+                ids_remapped: 0,
+                gaps_removed: 0,
+            };
+        }
+
+        let old_min_id = *self.tokenizer.model.vocab.values().min().unwrap();
+        let old_max_id = *self.tokenizer.model.vocab.values().max().unwrap();
+
+        let mut normal: Vec<(String, u32)> = vec![];
+        let mut reserved_entries: Vec<(String, u32)> = vec![];
+        for (token, &id) in &self.tokenizer.model.vocab {
+            if self.special_tokens.reserved.get(token) == Some(&id) {
+                reserved_entries.push((token.clone(), id));
+            } else {
+                normal.push((token.clone(), id));
+            }
+        }
+        normal.sort_by_key(|(_, id)| *id);
+
+        let reserved_ids: std::collections::HashSet<u32> =
+            reserved_entries.iter().map(|(_, id)| *id).collect();
+        let mut available_ids = (0u32..).filter(|id| !reserved_ids.contains(id));
+
+        let mut new_vocab = std::collections::BTreeMap::new();
+        let mut ids_remapped = 0usize;
+        for (token, old_id) in normal {
+            let new_id = available_ids.next().unwrap();
+            if new_id != old_id {
+                ids_remapped += 1;
+            }
+            new_vocab.insert(token, new_id);
+        }
+        for (token, id) in reserved_entries {
+            new_vocab.insert(token, id);
+        }
+
+        self.tokenizer.model.vocab = new_vocab;
+
+        self.used_ids.clear();
+        self.index2token.clear();
+        for (token, &id) in &self.tokenizer.model.vocab {
+            self.used_ids.insert(id);
+            self.index2token.insert(id, token.clone());
+        }
+        self.next_id = self.used_ids.iter().max().copied().unwrap_or(0) + 1;
+
+        let new_min_id = *self.tokenizer.model.vocab.values().min().unwrap();
+        let new_max_id = *self.tokenizer.model.vocab.values().max().unwrap();
+        let gaps_before = (old_max_id as usize + 1).saturating_sub(vocab_size);
+        let gaps_after = (new_max_id as usize + 1).saturating_sub(vocab_size);
+
+        ReindexResult {
+            vocab_size,
+            merges_count,
+            old_min_id,
+            old_max_id,
+            new_min_id,
+            new_max_id,
+            ids_remapped,
+            gaps_removed: gaps_before.saturating_sub(gaps_after),
+        }
+    }
+
     /// Check if vocabulary has gaps in its ID space
     ///
     /// Returns (has_gaps, total_gaps, min_id, max_id)
@@ -160,41 +250,7 @@ impl BPETokenizerEditor {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::tokenizer::{Merge, Model, Tokenizer};
-    use std::collections::BTreeMap;
-
-    fn create_test_tokenizer(vocab: Vec<(&str, u32)>, merges: Vec<(&str, &str)>) -> Tokenizer {
-        let vocab_map: BTreeMap<String, u32> =
-            vocab.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
-
-        let merge_list: Vec<Merge> = merges
-            .into_iter()
-            .map(|(a, b)| Merge(a.to_string(), b.to_string()))
-            .collect();
-
-        Tokenizer {
-            version: "1.0".to_string(),
-            truncation: None,
-            padding: None,
-            added_tokens: vec![],
-            normalizer: None,
-            pre_tokenizer: None,
-            post_processor: None,
-            decoder: None,
-            model: Model {
-                model_type: "BPE".to_string(),
-                dropout: None,
-                unk_token: "<unk>".to_string(),
-                continuing_subword_prefix: None,
-                end_of_word_suffix: None,
-                fuse_unk: false,
-                byte_fallback: false,
-                ignore_merges: false,
-                vocab: vocab_map,
-                merges: merge_list,
-            },
-        }
-    }
+    use super::super::test_support::create_test_tokenizer;
 
     #[test]
     fn test_reindex_with_gaps() {
@@ -7,8 +7,19 @@ use crate::types::RemovalResult;
 use super::core::BPETokenizerEditor;
 
 impl BPETokenizerEditor {
-    /// Remove a token and all its dependencies (merges that use it, etc.)
+    /// Remove a token and all its dependencies (merges that use it, etc.).
+    /// Refuses (no-op) if `token` is a reserved special token - callers that
+    /// need to repurpose a reserved slot should use `reassign_token`
+    /// instead of destroying and re-minting it.
     pub fn remove_token_and_dependencies(&mut self, token: &str) -> RemovalResult {
+        if self.special_tokens.reserved.contains_key(token) {
+            return RemovalResult {
+                root_token: token.to_string(),
+                removed_tokens: vec![],
+                removed_merges: vec![],
+            };
+        }
+
         let mut removed_tokens: HashSet<String> = HashSet::new();
         let mut removed_merge_indices: HashSet<usize> = HashSet::new();
         let mut stack = vec![token.to_string()];
@@ -24,7 +35,8 @@ impl BPETokenizerEditor {
                 for &mi in indices {
                     if !removed_merge_indices.contains(&mi) {
                         removed_merge_indices.insert(mi);
-                        let prod = self.tokenizer.model.merges[mi].result();
+                        let merge = &self.tokenizer.model.merges[mi];
+                        let prod = self.merge_result(&merge.0, &merge.1);
                         stack.push(prod);
                     }
                 }
@@ -45,6 +57,13 @@ impl BPETokenizerEditor {
             })
             .collect();
 
+        // Snapshot IDs before removing, so the revision can re-insert each
+        // token at its original ID on undo.
+        let removed_tokens_with_ids: Vec<(String, u32)> = removed_tokens
+            .iter()
+            .filter_map(|t| self.tokenizer.model.vocab.get(t).map(|&id| (t.clone(), id)))
+            .collect();
+
         // Remove merges
         if !removed_merge_indices.is_empty() {
             self.tokenizer.model.merges = self
@@ -60,14 +79,19 @@ impl BPETokenizerEditor {
 
         // Remove tokens from vocab
         for t in &removed_tokens {
-            if let Some(&id) = self.tokenizer.model.vocab.get(t) {
-                self.release_id(id);
-                self.tokenizer.model.vocab.remove(t);
-            }
+            self.remove_vocab_entry(t);
         }
 
         self.rebuild_indices();
 
+        self.record_revision(
+            format!("remove_token_and_dependencies('{}')", token),
+            vec![],
+            vec![],
+            removed_tokens_with_ids,
+            removed_merges.clone(),
+        );
+
         RemovalResult {
             root_token: token.to_string(),
             removed_tokens: removed_tokens.into_iter().collect(),
@@ -0,0 +1,289 @@
+//! Normalizes tokens between different BPE affixing/byte-level conventions
+//! (WordPiece `##`-style continuing-subword prefixes, suffix markers like
+//! `</w>`, and GPT-2-style byte-level `Ġ`/full-byte-remap encoding), so sync
+//! operations can move a token minted under one tokenizer's convention into
+//! another's without it silently landing as an unreachable, misspelled
+//! vocab entry.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use super::core::BPETokenizerEditor;
+
+/// A tokenizer's affixing/byte-level convention, as read off its model
+/// config and pre-tokenizer (or supplied by the caller when auto-detection
+/// isn't reliable, e.g. via `--source-scheme`/`--target-scheme`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AffixScheme {
+    pub continuing_subword_prefix: Option<String>,
+    pub end_of_word_suffix: Option<String>,
+    pub byte_level: bool,
+}
+
+fn byte_to_unicode() -> &'static HashMap<u8, char> {
+    static MAP: OnceLock<HashMap<u8, char>> = OnceLock::new();
+    MAP.get_or_init(|| {
+        // Standard GPT-2 byte-level alphabet: printable Latin-1 bytes map to
+        // themselves, every other byte gets a private spot above U+00FF, so
+        // every possible byte has a distinct, printable code point.
+        let mut bytes: Vec<u8> = (b'!'..=b'~').chain(0xA1..=0xAC).chain(0xAE..=0xFF).collect();
+        let mut points: Vec<u32> = bytes.iter().map(|&b| b as u32).collect();
+
+        let mut n = 0u32;
+        for b in 0u16..=255 {
+            let b = b as u8;
+            if !bytes.contains(&b) {
+                bytes.push(b);
+                points.push(256 + n);
+                n += 1;
+            }
+        }
+
+        bytes
+            .into_iter()
+            .zip(points.into_iter())
+            .map(|(b, c)| (b, char::from_u32(c).expect("valid GPT-2 byte-level code point")))
+            .collect()
+    })
+}
+
+fn unicode_to_byte() -> &'static HashMap<char, u8> {
+    static MAP: OnceLock<HashMap<char, u8>> = OnceLock::new();
+    MAP.get_or_init(|| byte_to_unicode().iter().map(|(&b, &c)| (c, b)).collect())
+}
+
+/// Decode a GPT-2-style byte-level token back to raw UTF-8 text. Fails if a
+/// character falls outside the byte-level alphabet, or the decoded bytes
+/// aren't valid UTF-8.
+fn decode_byte_level(token: &str) -> Option<String> {
+    let rev = unicode_to_byte();
+    let mut bytes = Vec::with_capacity(token.len());
+    for ch in token.chars() {
+        bytes.push(*rev.get(&ch)?);
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Encode raw UTF-8 text as a GPT-2-style byte-level token. Always
+/// succeeds, since the byte-level alphabet covers every possible byte.
+pub(crate) fn encode_byte_level(text: &str) -> String {
+    let map = byte_to_unicode();
+    text.bytes().map(|b| map[&b]).collect()
+}
+
+impl BPETokenizerEditor {
+    /// Read this tokenizer's affixing/byte-level convention off its model
+    /// config. `byte_level` is detected from the pre-tokenizer blob (the
+    /// crate otherwise leaves it as an opaque `serde_json::Value`, since
+    /// nothing else needs to interpret it).
+    pub fn affix_scheme(&self) -> AffixScheme {
+        let byte_level = self
+            .tokenizer
+            .pre_tokenizer
+            .as_ref()
+            .map(|v| v.to_string().contains("ByteLevel"))
+            .unwrap_or(false);
+
+        AffixScheme {
+            continuing_subword_prefix: self.tokenizer.model.continuing_subword_prefix.clone(),
+            end_of_word_suffix: self.tokenizer.model.end_of_word_suffix.clone(),
+            byte_level,
+        }
+    }
+}
+
+/// Translate `token`, written in `source`'s convention, into `target`'s
+/// convention (typically `target_editor.affix_scheme()`, or a
+/// `--target-scheme` override). Returns `None` if no faithful translation
+/// exists - currently only when `source` is byte-level and `token` either
+/// contains a character outside the byte-level alphabet or decodes to bytes
+/// that aren't valid UTF-8 (e.g. part of a multi-byte character).
+pub fn translate_token(token: &str, source: &AffixScheme, target: &AffixScheme) -> Option<String> {
+    if source == target {
+        return Some(token.to_string());
+    }
+
+    let mut rest = token;
+    let mut is_continuation = false;
+    let mut is_end_of_word = false;
+
+    if let Some(suffix) = source
+        .end_of_word_suffix
+        .as_deref()
+        .filter(|s| !s.is_empty())
+    {
+        if let Some(stripped) = rest.strip_suffix(suffix) {
+            rest = stripped;
+            is_end_of_word = true;
+        }
+    }
+    if let Some(prefix) = source
+        .continuing_subword_prefix
+        .as_deref()
+        .filter(|p| !p.is_empty())
+    {
+        if let Some(stripped) = rest.strip_prefix(prefix) {
+            rest = stripped;
+            is_continuation = true;
+        }
+    }
+
+    let raw = if source.byte_level {
+        decode_byte_level(rest)?
+    } else {
+        rest.to_string()
+    };
+
+    if target.byte_level {
+        return Some(encode_byte_level(&raw));
+    }
+
+    let mut out = raw;
+    if is_continuation {
+        if let Some(prefix) = target
+            .continuing_subword_prefix
+            .as_deref()
+            .filter(|p| !p.is_empty())
+        {
+            out = format!("{}{}", prefix, out);
+        }
+    }
+    if is_end_of_word {
+        if let Some(suffix) = target
+            .end_of_word_suffix
+            .as_deref()
+            .filter(|s| !s.is_empty())
+        {
+            out = format!("{}{}", out, suffix);
+        }
+    }
+
+    Some(out)
+}
+
+/// Split `word` into the single-symbol-per-character sequence a BPE merge
+/// loop would start from under `scheme`'s convention - the same marking
+/// `build_char_chain_marked` applies when hand-adding a token: the first
+/// character is unmarked, every other character carries
+/// `continuing_subword_prefix` (e.g. WordPiece's `##`), and - when set -
+/// the last character also carries `end_of_word_suffix`. Byte-level schemes
+/// instead run the whole word through the GPT-2-style byte-level alphabet
+/// and treat each resulting code point as its own symbol, so merges/vocab
+/// lookups land in that convention rather than a convention-less marker.
+pub(crate) fn mark_word_chars(scheme: &AffixScheme, word: &str) -> Vec<String> {
+    if scheme.byte_level {
+        return encode_byte_level(word).chars().map(|c| c.to_string()).collect();
+    }
+
+    let chars: Vec<char> = word.chars().collect();
+    let last_idx = chars.len().saturating_sub(1);
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, &ch)| {
+            let mut s = ch.to_string();
+            if i > 0 {
+                if let Some(prefix) = scheme
+                    .continuing_subword_prefix
+                    .as_deref()
+                    .filter(|p| !p.is_empty())
+                {
+                    s = format!("{}{}", prefix, s);
+                }
+            }
+            if i == last_idx {
+                if let Some(suffix) = scheme.end_of_word_suffix.as_deref().filter(|s| !s.is_empty()) {
+                    s = format!("{}{}", s, suffix);
+                }
+            }
+            s
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wordpiece_scheme() -> AffixScheme {
+        AffixScheme {
+            continuing_subword_prefix: Some("##".to_string()),
+            end_of_word_suffix: None,
+            byte_level: false,
+        }
+    }
+
+    fn plain_scheme() -> AffixScheme {
+        AffixScheme {
+            continuing_subword_prefix: None,
+            end_of_word_suffix: None,
+            byte_level: false,
+        }
+    }
+
+    fn suffix_scheme() -> AffixScheme {
+        AffixScheme {
+            continuing_subword_prefix: None,
+            end_of_word_suffix: Some("</w>".to_string()),
+            byte_level: false,
+        }
+    }
+
+    fn byte_level_scheme() -> AffixScheme {
+        AffixScheme {
+            continuing_subword_prefix: None,
+            end_of_word_suffix: None,
+            byte_level: true,
+        }
+    }
+
+    #[test]
+    fn test_translate_token_identical_schemes_is_passthrough() {
+        let scheme = wordpiece_scheme();
+        assert_eq!(
+            translate_token("##ab", &scheme, &scheme),
+            Some("##ab".to_string())
+        );
+    }
+
+    #[test]
+    fn test_translate_token_strips_wordpiece_prefix_for_plain_target() {
+        assert_eq!(
+            translate_token("##ab", &wordpiece_scheme(), &plain_scheme()),
+            Some("ab".to_string())
+        );
+    }
+
+    #[test]
+    fn test_translate_token_adds_wordpiece_prefix_for_plain_source() {
+        assert_eq!(
+            translate_token("ab", &plain_scheme(), &wordpiece_scheme()),
+            Some("##ab".to_string())
+        );
+    }
+
+    #[test]
+    fn test_translate_token_converts_suffix_marker_between_schemes() {
+        assert_eq!(
+            translate_token("ab</w>", &suffix_scheme(), &wordpiece_scheme()),
+            Some("ab".to_string())
+        );
+    }
+
+    #[test]
+    fn test_translate_token_round_trips_through_byte_level() {
+        let encoded = translate_token("café", &plain_scheme(), &byte_level_scheme()).unwrap();
+        let decoded = translate_token(&encoded, &byte_level_scheme(), &plain_scheme()).unwrap();
+        assert_eq!(decoded, "café");
+    }
+
+    #[test]
+    fn test_translate_token_rejects_out_of_alphabet_byte_level_source() {
+        // A token containing a char outside the byte-level alphabet has no
+        // faithful decoding.
+        assert_eq!(
+            translate_token("not-byte-level-japan-\u{3042}", &byte_level_scheme(), &plain_scheme()),
+            None
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! First-class special-token registry
+//!
+//! Replaces the old `<...>`/`[...]` string-shape heuristics with an explicit
+//! registry populated from the tokenizer JSON's `added_tokens` array (plus
+//! any named roles tracked on the model, like the unk token). Registered
+//! tokens are never chosen by the vocab-size pruning heuristics regardless
+//! of their shape or length. Tokens registered via `reserve_special_token`
+//! additionally get a pinned ID that `reindex_vocab` will never remap.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Result};
+
+use super::core::BPETokenizerEditor;
+
+/// Registry of tokens that should be treated as special (never pruned).
+#[derive(Debug, Clone, Default)]
+pub struct SpecialTokens {
+    /// All registered special token strings.
+    pub(crate) tokens: HashSet<String>,
+    /// Named roles (e.g. "pad", "unk", "bos", "eos", "sep") -> token string.
+    pub(crate) roles: HashMap<String, String>,
+    /// Tokens pinned to a specific ID, exempt from `reindex_vocab` remaps.
+    pub(crate) reserved: HashMap<String, u32>,
+}
+
+impl SpecialTokens {
+    /// Build a registry from a tokenizer's `added_tokens` array and its
+    /// model-level `unk_token`. `added_tokens` entries are treated as
+    /// already-reserved: their `id` is pinned the same way
+    /// `reserve_special_token` pins one.
+    pub(crate) fn from_tokenizer(tokenizer: &crate::tokenizer::Tokenizer) -> Self {
+        let mut registry = SpecialTokens::default();
+
+        for entry in &tokenizer.added_tokens {
+            if let Some(content) = entry.get("content").and_then(|v| v.as_str()) {
+                registry.tokens.insert(content.to_string());
+                if let Some(id) = entry.get("id").and_then(|v| v.as_u64()) {
+                    registry.reserved.insert(content.to_string(), id as u32);
+                }
+            }
+        }
+
+        if !tokenizer.model.unk_token.is_empty() {
+            registry.register_role("unk", &tokenizer.model.unk_token);
+        }
+
+        registry
+    }
+
+    /// All registered special tokens.
+    pub fn tokens(&self) -> &HashSet<String> {
+        &self.tokens
+    }
+
+    /// Look up the token assigned to a named role (e.g. "pad", "bos").
+    pub fn role(&self, role: &str) -> Option<&str> {
+        self.roles.get(role).map(|s| s.as_str())
+    }
+
+    /// Check whether a token is registered as special, under any role.
+    pub fn contains(&self, token: &str) -> bool {
+        self.tokens.contains(token)
+    }
+
+    /// Tokens with a pinned ID, exempt from `reindex_vocab` remaps.
+    pub fn reserved(&self) -> &HashMap<String, u32> {
+        &self.reserved
+    }
+}
+
+impl BPETokenizerEditor {
+    /// Register `token` as a special token, optionally under a named role
+    /// (e.g. "pad", "unk", "bos", "eos", "sep").
+    pub fn register_special_token(&mut self, token: &str, role: Option<&str>) {
+        self.special_tokens.tokens.insert(token.to_string());
+        if let Some(role) = role {
+            self.special_tokens
+                .roles
+                .insert(role.to_string(), token.to_string());
+        }
+    }
+
+    /// Unregister a special token. If it was the holder of any named role,
+    /// that role is cleared too, and its ID is no longer pinned.
+    pub fn unregister_special_token(&mut self, token: &str) {
+        self.special_tokens.tokens.remove(token);
+        self.special_tokens.roles.retain(|_, v| v != token);
+        self.special_tokens.reserved.remove(token);
+    }
+
+    /// Check whether a token is registered as special.
+    pub fn is_special_token(&self, token: &str) -> bool {
+        self.special_tokens.contains(token)
+    }
+
+    /// Register `token` as special and pin it to `id`, guaranteeing
+    /// `reindex_vocab` will never remap it. If `token` already exists in
+    /// vocab, its current ID must match `id`. If `token` is new, `id` must
+    /// not already be in use.
+    pub fn reserve_special_token(&mut self, token: &str, id: u32, role: Option<&str>) -> Result<()> {
+        match self.tokenizer.model.vocab.get(token).copied() {
+            Some(existing_id) if existing_id != id => {
+                bail!(
+                    "Token '{}' already exists at ID {}, not {}; use reassign_token first",
+                    token,
+                    existing_id,
+                    id
+                );
+            }
+            Some(_) => {}
+            None => {
+                if self.used_ids.contains(&id) {
+                    bail!("ID {} is already used by another token", id);
+                }
+                self.used_ids.insert(id);
+                self.insert_vocab_entry(token.to_string(), id);
+            }
+        }
+
+        self.register_special_token(token, role);
+        self.special_tokens.reserved.insert(token.to_string(), id);
+        Ok(())
+    }
+}
+
+impl SpecialTokens {
+    fn register_role(&mut self, role: &str, token: &str) {
+        self.tokens.insert(token.to_string());
+        self.roles.insert(role.to_string(), token.to_string());
+    }
+}
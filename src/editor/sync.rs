@@ -6,14 +6,21 @@ use crate::types::{
     CharAddInfo, ShortTokenAddInfo, SyncCharsResult, SyncShortTokensResult, TokenRemovalInfo,
 };
 
-use super::core::BPETokenizerEditor;
+use super::core::{compute_merge_result, BPETokenizerEditor};
+use super::scheme::{translate_token, AffixScheme};
 
 impl BPETokenizerEditor {
-    /// Sync single-letter tokens from source, removing longest tokens to keep size constant
+    /// Sync single-letter tokens from source, removing longest tokens to keep size constant.
+    /// `source_scheme` is the source tokenizer's affixing/byte-level
+    /// convention; each source char is translated into `target_scheme`
+    /// (normally `self.affix_scheme()`) before being added, and chars with
+    /// no faithful translation are recorded in `chars_skipped` instead.
     pub fn sync_single_chars(
         &mut self,
         source_chars: &[(String, u32)],
         min_id: u32,
+        source_scheme: &AffixScheme,
+        target_scheme: &AffixScheme,
     ) -> SyncCharsResult {
         let initial_vocab_size = self.vocab_size();
         let initial_merges_count = self.merges_count();
@@ -29,15 +36,23 @@ impl BPETokenizerEditor {
             tokens_removed: vec![],
             total_tokens_removed: 0,
             total_merges_removed: 0,
+            chars_skipped: vec![],
         };
 
-        let chars_to_add: Vec<_> = source_chars
-            .iter()
-            .filter(|(char_tok, _)| !self.has_token(char_tok))
-            .cloned()
-            .collect();
+        let mut chars_to_add: Vec<(String, u32)> = vec![];
+        for (char_tok, source_id) in source_chars {
+            let Some(translated) = translate_token(char_tok, source_scheme, target_scheme) else {
+                result.chars_skipped.push(char_tok.clone());
+                continue;
+            };
+
+            if self.has_token(&translated) {
+                result.chars_already_present += 1;
+            } else {
+                chars_to_add.push((translated, *source_id));
+            }
+        }
 
-        result.chars_already_present = source_chars.len() - chars_to_add.len();
         let total_to_add = chars_to_add.len();
 
         if total_to_add == 0 {
@@ -146,12 +161,19 @@ impl BPETokenizerEditor {
         result
     }
 
-    /// Sync short tokens (2-3 chars) from source, including their merges
+    /// Sync short tokens (2-3 chars) from source, including their merges.
+    /// `source_scheme` is the source tokenizer's affixing/byte-level
+    /// convention; each source token (and the operands of its producing
+    /// merge, if any) is translated into `target_scheme` (normally
+    /// `self.affix_scheme()`) before being added, and tokens with no faithful translation are
+    /// recorded in `tokens_skipped` instead.
     pub fn sync_short_tokens(
         &mut self,
         source_tokens: &[(String, u32)],
         source_merges: &[(String, String)],
         min_id: u32,
+        source_scheme: &AffixScheme,
+        target_scheme: &AffixScheme,
     ) -> SyncShortTokensResult {
         let initial_vocab_size = self.vocab_size();
         let initial_merges_count = self.merges_count();
@@ -169,24 +191,60 @@ impl BPETokenizerEditor {
             tokens_removed: vec![],
             total_tokens_removed: 0,
             total_merges_removed: 0,
+            tokens_skipped: vec![],
         };
 
-        // Build source merge map: result -> (a, b)
+        // Build source merge map: result -> (a, b), keyed in the source's
+        // own convention (translated alongside the token that needs it).
+        // The key must be the prefix-aware merge result, not a naive
+        // concatenation, or affix-marked tokens (e.g. WordPiece `##`) never
+        // match their producing merge.
         let source_merge_map: HashMap<String, (String, String)> = source_merges
             .iter()
-            .map(|(a, b)| (format!("{}{}", a, b), (a.clone(), b.clone())))
+            .map(|(a, b)| {
+                let result = compute_merge_result(
+                    source_scheme.continuing_subword_prefix.as_deref(),
+                    a,
+                    b,
+                );
+                (result, (a.clone(), b.clone()))
+            })
             .collect();
 
         let current_merges = self.get_merge_set();
 
-        let tokens_to_add: Vec<_> = source_tokens
-            .iter()
-            .filter(|(tok, _)| !self.has_token(tok))
-            .cloned()
-            .collect();
+        struct PendingToken {
+            translated: String,
+            source_id: u32,
+            translated_merge: Option<(String, String)>,
+        }
+
+        let mut pending: Vec<PendingToken> = vec![];
+        for (tok, source_id) in source_tokens {
+            let Some(translated) = translate_token(tok, source_scheme, target_scheme) else {
+                result.tokens_skipped.push(tok.clone());
+                continue;
+            };
+
+            if self.has_token(&translated) {
+                result.tokens_already_present += 1;
+                continue;
+            }
 
-        result.tokens_already_present = source_tokens.len() - tokens_to_add.len();
-        let total_to_add = tokens_to_add.len();
+            let translated_merge = source_merge_map.get(tok).and_then(|(a, b)| {
+                let ta = translate_token(a, source_scheme, target_scheme)?;
+                let tb = translate_token(b, source_scheme, target_scheme)?;
+                Some((ta, tb))
+            });
+
+            pending.push(PendingToken {
+                translated,
+                source_id: *source_id,
+                translated_merge,
+            });
+        }
+
+        let total_to_add = pending.len();
 
         if total_to_add == 0 {
             result.final_vocab_size = self.vocab_size();
@@ -259,9 +317,9 @@ impl BPETokenizerEditor {
         let start_time = std::time::Instant::now();
         let mut last_print = std::time::Instant::now();
 
-        for (i, (token, source_id)) in tokens_to_add.iter().enumerate() {
+        for (i, p) in pending.iter().enumerate() {
             // Check if we need to add the merge that produces this token
-            if let Some((a, b)) = source_merge_map.get(token) {
+            if let Some((a, b)) = &p.translated_merge {
                 if !self.has_token(a) {
                     self.add_token_atomic(a);
                 }
@@ -278,11 +336,11 @@ impl BPETokenizerEditor {
                 }
             }
 
-            self.add_token_atomic(token);
+            self.add_token_atomic(&p.translated);
             result.tokens_added.push(ShortTokenAddInfo {
-                token: token.clone(),
-                source_id: *source_id,
-                length: token.chars().count(),
+                token: p.translated.clone(),
+                source_id: p.source_id,
+                length: p.translated.chars().count(),
             });
 
             let now = std::time::Instant::now();
@@ -321,3 +379,95 @@ impl BPETokenizerEditor {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::test_support::create_test_tokenizer;
+
+    fn wordpiece_scheme() -> AffixScheme {
+        AffixScheme {
+            continuing_subword_prefix: Some("##".to_string()),
+            end_of_word_suffix: None,
+            byte_level: false,
+        }
+    }
+
+    fn plain_scheme() -> AffixScheme {
+        AffixScheme {
+            continuing_subword_prefix: None,
+            end_of_word_suffix: None,
+            byte_level: false,
+        }
+    }
+
+    #[test]
+    fn test_sync_single_chars_translates_across_schemes() {
+        let mut target = BPETokenizerEditor::new(create_test_tokenizer(
+            vec![("a", 0), ("b", 1), ("ab", 2)],
+            vec![("a", "b")],
+        ));
+        // "a" already exists; "##c" is a WordPiece-continuation single char
+        // that should translate to plain "c" in the target's convention.
+        let source_chars = vec![("a".to_string(), 0), ("##c".to_string(), 1)];
+
+        let result = target.sync_single_chars(
+            &source_chars,
+            0,
+            &wordpiece_scheme(),
+            &plain_scheme(),
+        );
+
+        assert_eq!(result.chars_already_present, 1);
+        assert_eq!(result.chars_added.len(), 1);
+        assert_eq!(result.chars_added[0].char_token, "c");
+        assert!(target.has_token("c"));
+    }
+
+    #[test]
+    fn test_sync_short_tokens_keys_merge_map_with_prefix_aware_result() {
+        // Regression test: the merge map must be keyed by the prefix-aware
+        // merge result ("##ab"), not a naive concatenation ("##a##b"), or
+        // this WordPiece-marked token never finds its producing merge.
+        let mut target = BPETokenizerEditor::new(create_test_tokenizer(vec![("x", 0)], vec![]));
+        let source_tokens = vec![("##ab".to_string(), 5)];
+        let source_merges = vec![("##a".to_string(), "##b".to_string())];
+
+        let result = target.sync_short_tokens(
+            &source_tokens,
+            &source_merges,
+            0,
+            &wordpiece_scheme(),
+            &wordpiece_scheme(),
+        );
+
+        assert_eq!(result.merges_added, 1);
+        assert!(target.has_token("##ab"));
+        assert!(target
+            .get_merge_set()
+            .contains(&("##a".to_string(), "##b".to_string())));
+    }
+
+    #[test]
+    fn test_sync_short_tokens_skips_tokens_with_no_faithful_translation() {
+        let mut target = BPETokenizerEditor::new(create_test_tokenizer(vec![("x", 0)], vec![]));
+        let byte_level = AffixScheme {
+            continuing_subword_prefix: None,
+            end_of_word_suffix: None,
+            byte_level: true,
+        };
+        // A char outside the byte-level alphabet can't be faithfully decoded.
+        let source_tokens = vec![("\u{3042}".to_string(), 1)];
+
+        let result = target.sync_short_tokens(
+            &source_tokens,
+            &[],
+            0,
+            &byte_level,
+            &plain_scheme(),
+        );
+
+        assert_eq!(result.tokens_skipped, vec!["\u{3042}".to_string()]);
+        assert_eq!(result.tokens_added.len(), 0);
+    }
+}
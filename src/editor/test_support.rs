@@ -0,0 +1,43 @@
+//! Shared `Tokenizer` fixture for editor submodule tests, so each doesn't
+//! hand-roll its own near-identical builder.
+
+use std::collections::BTreeMap;
+
+use crate::tokenizer::{Merge, Model, Tokenizer};
+
+/// Build a minimal BPE `Tokenizer` from `vocab`/`merges`, with every other
+/// field (normalizer, pre_tokenizer, affixes, byte_fallback, ...) left at
+/// its off/default value. Callers that need a non-default field mutate the
+/// returned `Tokenizer` directly (e.g. `tokenizer.model.continuing_subword_prefix = ...`).
+pub(crate) fn create_test_tokenizer(vocab: Vec<(&str, u32)>, merges: Vec<(&str, &str)>) -> Tokenizer {
+    let vocab_map: BTreeMap<String, u32> =
+        vocab.into_iter().map(|(k, v)| (k.to_string(), v)).collect();
+
+    let merge_list: Vec<Merge> = merges
+        .into_iter()
+        .map(|(a, b)| Merge(a.to_string(), b.to_string()))
+        .collect();
+
+    Tokenizer {
+        version: "1.0".to_string(),
+        truncation: None,
+        padding: None,
+        added_tokens: vec![],
+        normalizer: None,
+        pre_tokenizer: None,
+        post_processor: None,
+        decoder: None,
+        model: Model {
+            model_type: "BPE".to_string(),
+            dropout: None,
+            unk_token: "<unk>".to_string(),
+            continuing_subword_prefix: None,
+            end_of_word_suffix: None,
+            fuse_unk: false,
+            byte_fallback: false,
+            ignore_merges: false,
+            vocab: vocab_map,
+            merges: merge_list,
+        },
+    }
+}
@@ -0,0 +1,498 @@
+//! Corpus-driven BPE merge learning
+//!
+//! Grows or refills the vocabulary by learning new merges from raw text,
+//! the way a BPE trainer does, rather than only inserting hand-supplied
+//! tokens. Uses a lazy-deletion `BinaryHeap` of pair candidates so each
+//! merge only rescans the words it actually touched, instead of
+//! recounting every pair in the corpus on every iteration.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use crate::tokenizer::Merge;
+use crate::types::TrainResult;
+
+use super::core::BPETokenizerEditor;
+use super::scheme::mark_word_chars;
+
+/// Configuration for `train_from_corpus`.
+#[derive(Debug, Clone)]
+pub struct TrainConfig {
+    /// Stop once the vocab reaches this size.
+    pub vocab_size: usize,
+    /// Minimum pair frequency required to add a merge.
+    pub min_frequency: u32,
+    /// Stop after this many new merges, even if `vocab_size` isn't reached.
+    pub max_merges: Option<usize>,
+    /// Extra single-character symbols to seed the alphabet with, beyond
+    /// whatever's already in vocab and whatever appears in the corpus.
+    pub initial_alphabet: Vec<String>,
+    /// Cap the base alphabet to its most frequent symbols, mirroring HF's
+    /// `limit_alphabet`.
+    pub limit_alphabet: Option<usize>,
+}
+
+impl Default for TrainConfig {
+    fn default() -> Self {
+        Self {
+            vocab_size: 0,
+            min_frequency: 2,
+            max_merges: None,
+            initial_alphabet: vec![],
+            limit_alphabet: None,
+        }
+    }
+}
+
+/// A candidate pair in the training heap, ordered by count (ties broken by
+/// lexicographically smaller pair, for determinism).
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct PairCandidate {
+    count: u64,
+    pair: (String, String),
+}
+
+impl Ord for PairCandidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.count
+            .cmp(&other.count)
+            .then_with(|| other.pair.cmp(&self.pair))
+    }
+}
+
+impl PartialOrd for PairCandidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn decrement_pair(
+    pair_counts: &mut HashMap<(String, String), u64>,
+    heap: &mut BinaryHeap<PairCandidate>,
+    key: &(String, String),
+    by: u64,
+) {
+    if let Some(count) = pair_counts.get_mut(key) {
+        *count = count.saturating_sub(by);
+        if *count == 0 {
+            pair_counts.remove(key);
+        } else {
+            heap.push(PairCandidate { count: *count, pair: key.clone() });
+        }
+    }
+}
+
+impl BPETokenizerEditor {
+    /// Learn new BPE merges from raw text, growing the vocabulary toward
+    /// `config.vocab_size`.
+    ///
+    /// Builds a word -> frequency map from the corpus, represents each word
+    /// as a sequence of symbols marked per this editor's actual
+    /// `affix_scheme()` (so a WordPiece-configured tokenizer trains on
+    /// `"w"`, `"##o"`, `"##r"`, `"##d"`, not a convention-less SentencePiece
+    /// marker - see `scheme::mark_word_chars`), seeding the alphabet from existing
+    /// single-char tokens, `config.initial_alphabet`, and the corpus itself,
+    /// optionally capped by `config.limit_alphabet`. Then repeatedly pops
+    /// the highest count pair off a lazy-deletion heap, re-scoring only the
+    /// words that contained it. Stops when the heap empties,
+    /// `config.vocab_size` is reached, `config.max_merges` new merges have
+    /// been added, or the top count drops below `config.min_frequency`.
+    /// Merged strings are formed via `merge_result`, so
+    /// `continuing_subword_prefix` is respected the same way hand-derived
+    /// merges are. A candidate pair already present in `self.tokenizer`'s
+    /// merge table (e.g. when topping up a vocab that was already trained)
+    /// still folds its word occurrences into the running symbol sequences,
+    /// but is not pushed again, so resuming training never duplicates rows.
+    pub fn train_from_corpus(&mut self, texts: &[String], config: TrainConfig) -> TrainResult {
+        let initial_vocab_size = self.vocab_size();
+        let scheme = self.affix_scheme();
+
+        let mut word_freq: HashMap<String, u64> = HashMap::new();
+        for line in texts {
+            for word in line.split_whitespace() {
+                *word_freq.entry(word.to_string()).or_insert(0) += 1;
+            }
+        }
+
+        for extra in &config.initial_alphabet {
+            if !self.has_token(extra) {
+                self.add_token_atomic(extra);
+            }
+        }
+
+        let mut words: Vec<(Vec<String>, u64)> = Vec::with_capacity(word_freq.len());
+        let mut alphabet_counts: HashMap<String, u64> = HashMap::new();
+        for (word, freq) in word_freq {
+            let symbols = mark_word_chars(&scheme, &word);
+            for sym in &symbols {
+                *alphabet_counts.entry(sym.clone()).or_insert(0) += freq;
+            }
+            words.push((symbols, freq));
+        }
+
+        let mut alphabet: Vec<(String, u64)> = alphabet_counts.into_iter().collect();
+        alphabet.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        if let Some(limit) = config.limit_alphabet {
+            alphabet.truncate(limit);
+        }
+        for (sym, _) in &alphabet {
+            if !self.has_token(sym) {
+                self.add_token_atomic(sym);
+            }
+        }
+
+        // Pair counts plus a reverse index of which words contain each
+        // pair, so committing a merge only needs to revisit those words.
+        let mut pair_counts: HashMap<(String, String), u64> = HashMap::new();
+        let mut where_used: HashMap<(String, String), HashSet<usize>> = HashMap::new();
+        for (wi, (symbols, freq)) in words.iter().enumerate() {
+            for pair in symbols.windows(2) {
+                let key = (pair[0].clone(), pair[1].clone());
+                *pair_counts.entry(key.clone()).or_insert(0) += freq;
+                where_used.entry(key).or_default().insert(wi);
+            }
+        }
+
+        let mut heap: BinaryHeap<PairCandidate> = pair_counts
+            .iter()
+            .map(|(pair, &count)| PairCandidate {
+                count,
+                pair: pair.clone(),
+            })
+            .collect();
+
+        let mut merges_added = 0usize;
+        let mut tokens_added = 0usize;
+        let mut existing_merges: HashSet<(String, String)> = self.get_merge_set();
+
+        while self.vocab_size() < config.vocab_size {
+            if let Some(max) = config.max_merges {
+                if merges_added >= max {
+                    break;
+                }
+            }
+
+            let candidate = match heap.pop() {
+                Some(c) => c,
+                None => break,
+            };
+
+            // Lazy deletion: the heap can carry stale entries whose count
+            // no longer matches reality (an earlier merge touched the same
+            // pair). Skip those instead of recomputing the whole heap.
+            let live_count = pair_counts.get(&candidate.pair).copied().unwrap_or(0);
+            if live_count != candidate.count {
+                continue;
+            }
+            if live_count < config.min_frequency as u64 {
+                break;
+            }
+
+            let (left, right) = candidate.pair.clone();
+            let merged = self.merge_result(&left, &right);
+
+            if !self.has_token(&merged) {
+                let id = self.get_next_id();
+                self.insert_vocab_entry(merged.clone(), id);
+                tokens_added += 1;
+            }
+            // Don't re-push a merge that's already in the tokenizer's merge
+            // table (e.g. when topping up a vocab that already has it from
+            // an earlier training/add pass) - only the word-symbol updates
+            // below are needed to keep the heap state correct.
+            if existing_merges.insert((left.clone(), right.clone())) {
+                self.tokenizer
+                    .model
+                    .merges
+                    .push(Merge(left.clone(), right.clone()));
+                merges_added += 1;
+            }
+
+            let affected: Vec<usize> = where_used
+                .remove(&candidate.pair)
+                .map(|set| set.into_iter().collect())
+                .unwrap_or_default();
+
+            for wi in affected {
+                let (symbols, freq) = &mut words[wi];
+                let freq = *freq;
+                let mut i = 0;
+                while i + 1 < symbols.len() {
+                    if symbols[i] != left || symbols[i + 1] != right {
+                        i += 1;
+                        continue;
+                    }
+
+                    decrement_pair(&mut pair_counts, &mut heap, &candidate.pair, freq);
+                    if i > 0 {
+                        let before = (symbols[i - 1].clone(), symbols[i].clone());
+                        decrement_pair(&mut pair_counts, &mut heap, &before, freq);
+                        if let Some(set) = where_used.get_mut(&before) {
+                            set.remove(&wi);
+                        }
+                    }
+                    if i + 2 < symbols.len() {
+                        let after = (symbols[i + 1].clone(), symbols[i + 2].clone());
+                        decrement_pair(&mut pair_counts, &mut heap, &after, freq);
+                        if let Some(set) = where_used.get_mut(&after) {
+                            set.remove(&wi);
+                        }
+                    }
+
+                    symbols[i] = merged.clone();
+                    symbols.remove(i + 1);
+
+                    if i > 0 {
+                        let new_before = (symbols[i - 1].clone(), symbols[i].clone());
+                        *pair_counts.entry(new_before.clone()).or_insert(0) += freq;
+                        where_used.entry(new_before.clone()).or_default().insert(wi);
+                        heap.push(PairCandidate {
+                            count: pair_counts[&new_before],
+                            pair: new_before,
+                        });
+                    }
+                    if i + 1 < symbols.len() {
+                        let new_after = (symbols[i].clone(), symbols[i + 1].clone());
+                        *pair_counts.entry(new_after.clone()).or_insert(0) += freq;
+                        where_used.entry(new_after.clone()).or_default().insert(wi);
+                        heap.push(PairCandidate {
+                            count: pair_counts[&new_after],
+                            pair: new_after,
+                        });
+                    }
+                }
+            }
+        }
+
+        self.rebuild_indices();
+
+        TrainResult {
+            initial_vocab_size,
+            final_vocab_size: self.vocab_size(),
+            merges_added,
+            tokens_added,
+        }
+    }
+
+    /// Convenience wrapper over `train_from_corpus` taking the trainer's
+    /// parameters positionally, for callers (e.g. the PyO3 bindings) that
+    /// would rather not build a `TrainConfig` themselves.
+    pub fn train_merges(
+        &mut self,
+        corpus: &[String],
+        target_vocab_size: usize,
+        min_frequency: u32,
+        limit_alphabet: Option<usize>,
+    ) -> TrainResult {
+        self.train_from_corpus(
+            corpus,
+            TrainConfig {
+                vocab_size: target_vocab_size,
+                min_frequency,
+                limit_alphabet,
+                ..Default::default()
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::{Model, Tokenizer};
+    use std::collections::BTreeMap;
+
+    fn empty_tokenizer() -> Tokenizer {
+        Tokenizer {
+            version: "1.0".to_string(),
+            truncation: None,
+            padding: None,
+            added_tokens: vec![],
+            normalizer: None,
+            pre_tokenizer: None,
+            post_processor: None,
+            decoder: None,
+            model: Model {
+                model_type: "BPE".to_string(),
+                dropout: None,
+                unk_token: "<unk>".to_string(),
+                continuing_subword_prefix: None,
+                end_of_word_suffix: None,
+                fuse_unk: false,
+                byte_fallback: false,
+                ignore_merges: false,
+                vocab: BTreeMap::new(),
+                merges: vec![],
+            },
+        }
+    }
+
+    #[test]
+    fn test_train_learns_merge_from_repeated_pair() {
+        let mut editor = BPETokenizerEditor::new(empty_tokenizer());
+        let texts = vec!["ab ab ab".to_string()];
+
+        let result = editor.train_from_corpus(
+            &texts,
+            TrainConfig {
+                vocab_size: 100,
+                min_frequency: 2,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.merges_added > 0);
+        assert!(editor.has_token("ab"));
+        assert!(editor
+            .get_merge_set()
+            .contains(&("a".to_string(), "b".to_string())));
+    }
+
+    #[test]
+    fn test_train_respects_min_frequency() {
+        let mut editor = BPETokenizerEditor::new(empty_tokenizer());
+        // "ab" only occurs once, below min_frequency of 2, so no merge for it.
+        let texts = vec!["ab cd cd cd".to_string()];
+
+        editor.train_from_corpus(
+            &texts,
+            TrainConfig {
+                vocab_size: 100,
+                min_frequency: 2,
+                ..Default::default()
+            },
+        );
+
+        assert!(!editor.has_token("ab"));
+        assert!(editor.has_token("cd"));
+    }
+
+    #[test]
+    fn test_train_stops_at_vocab_size() {
+        let mut editor = BPETokenizerEditor::new(empty_tokenizer());
+        let texts = vec!["ab ab cd cd ef ef".to_string()];
+
+        // Alphabet seeding alone yields 6 symbols (a, b, c, d, e, f), so a
+        // target one above that allows exactly one merge in.
+        let result = editor.train_from_corpus(
+            &texts,
+            TrainConfig {
+                vocab_size: 7,
+                min_frequency: 1,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(editor.vocab_size(), 7);
+        assert_eq!(result.final_vocab_size, 7);
+        assert_eq!(result.merges_added, 1);
+    }
+
+    #[test]
+    fn test_train_stops_at_max_merges() {
+        let mut editor = BPETokenizerEditor::new(empty_tokenizer());
+        let texts = vec!["ab ab cd cd ef ef".to_string()];
+
+        let result = editor.train_from_corpus(
+            &texts,
+            TrainConfig {
+                vocab_size: 1000,
+                min_frequency: 1,
+                max_merges: Some(1),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(result.merges_added, 1);
+    }
+
+    #[test]
+    fn test_train_is_deterministic_across_runs() {
+        let texts = vec!["ab ab cd cd ef ef gh gh".to_string()];
+        let config = TrainConfig {
+            vocab_size: 1000,
+            min_frequency: 1,
+            ..Default::default()
+        };
+
+        let mut editor1 = BPETokenizerEditor::new(empty_tokenizer());
+        let result1 = editor1.train_from_corpus(&texts, config.clone());
+
+        let mut editor2 = BPETokenizerEditor::new(empty_tokenizer());
+        let result2 = editor2.train_from_corpus(&texts, config);
+
+        assert_eq!(result1.merges_added, result2.merges_added);
+        assert_eq!(result1.tokens_added, result2.tokens_added);
+        assert_eq!(editor1.tokenizer.model.merges.len(), editor2.tokenizer.model.merges.len());
+    }
+
+    fn wordpiece_tokenizer() -> Tokenizer {
+        let mut t = empty_tokenizer();
+        t.model.continuing_subword_prefix = Some("##".to_string());
+        t
+    }
+
+    #[test]
+    fn test_train_marks_continuation_chars_per_affix_scheme() {
+        // On a WordPiece-configured tokenizer, the trainer must seed "w"
+        // and "##o" (not a bare "o" or a SentencePiece "▁w"), so a learned
+        // merge lands in the convention the rest of the vocab already uses.
+        let mut editor = BPETokenizerEditor::new(wordpiece_tokenizer());
+        let texts = vec!["wo wo wo".to_string()];
+
+        let result = editor.train_from_corpus(
+            &texts,
+            TrainConfig {
+                vocab_size: 100,
+                min_frequency: 2,
+                ..Default::default()
+            },
+        );
+
+        assert!(result.merges_added > 0);
+        assert!(editor.has_token("w"));
+        assert!(editor.has_token("##o"));
+        assert!(!editor.has_token("o"));
+        assert!(editor
+            .get_merge_set()
+            .contains(&("w".to_string(), "##o".to_string())));
+    }
+
+    #[test]
+    fn test_train_does_not_duplicate_merges_already_in_vocab() {
+        // Regression test: topping up a vocab that already contains a
+        // merge for a pair the corpus keeps reinforcing must not push a
+        // second row for that same (left, right) pair.
+        let mut tokenizer = empty_tokenizer();
+        tokenizer.model.vocab = BTreeMap::from([
+            ("a".to_string(), 0),
+            ("b".to_string(), 1),
+            ("ab".to_string(), 2),
+        ]);
+        tokenizer.model.merges = vec![Merge("a".to_string(), "b".to_string())];
+
+        let mut editor = BPETokenizerEditor::new(tokenizer);
+        let texts = vec!["ab ab ab".to_string()];
+
+        let result = editor.train_from_corpus(
+            &texts,
+            TrainConfig {
+                vocab_size: 100,
+                min_frequency: 1,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(result.merges_added, 0);
+        assert_eq!(
+            editor
+                .tokenizer
+                .model
+                .merges
+                .iter()
+                .filter(|m| m.0 == "a" && m.1 == "b")
+                .count(),
+            1
+        );
+    }
+}
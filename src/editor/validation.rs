@@ -4,17 +4,34 @@ use std::collections::HashSet;
 
 use crate::tokenizer::Merge;
 
-use super::core::BPETokenizerEditor;
+use super::core::{compute_merge_result, BPETokenizerEditor};
 
 impl BPETokenizerEditor {
-    /// Validate all merges - check that the result of each merge exists in vocab
+    /// Validate all merges - check that the result of each merge exists in
+    /// vocab. Honors `continuing_subword_prefix`: a merge `(A, B)` produces
+    /// `A + strip_prefix(B)` rather than a naive `A + B`, since `B` already
+    /// carries the continuation marker in vocab. Also honors
+    /// `end_of_word_suffix`: a merge can legitimately produce the last piece
+    /// of a word, which is stored in vocab with the suffix appended, so the
+    /// suffixed form is accepted too.
     pub fn validate_merges(&self) -> (Vec<usize>, Vec<(usize, Merge)>) {
         let mut valid_indices = Vec::new();
         let mut invalid = Vec::new();
+        let prefix_cfg = self.tokenizer.model.continuing_subword_prefix.as_deref();
+        let suffix_cfg = self.tokenizer.model.end_of_word_suffix.as_deref();
 
         for (i, merge) in self.tokenizer.model.merges.iter().enumerate() {
-            let result = merge.result();
-            if self.tokenizer.model.vocab.contains_key(&result) {
+            let result = compute_merge_result(prefix_cfg, &merge.0, &merge.1);
+            let suffixed = suffix_cfg
+                .filter(|s| !s.is_empty())
+                .map(|s| format!("{}{}", result, s));
+
+            let is_valid = self.tokenizer.model.vocab.contains_key(&result)
+                || suffixed
+                    .as_ref()
+                    .is_some_and(|s| self.tokenizer.model.vocab.contains_key(s));
+
+            if is_valid {
                 valid_indices.push(i);
             } else {
                 invalid.push((i, merge.clone()));
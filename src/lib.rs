@@ -8,7 +8,10 @@ pub mod types;
 
 // Editor is split into submodules
 mod editor;
-pub use editor::BPETokenizerEditor;
+pub use editor::{
+    AffixScheme, BPETokenizerEditor, HistoryLog, MergeConflictStrategy, Revision, RevisionId,
+    TrainConfig,
+};
 
 // Python bindings (only compiled with the python feature)
 #[cfg(feature = "python")]
@@ -6,8 +6,10 @@ use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
 
-use bpe_tokenizer_editor::cli::{Args, Commands};
-use bpe_tokenizer_editor::BPETokenizerEditor;
+use bpe_tokenizer_editor::cli::{Args, Commands, MergePreference, SchemeOverride};
+use bpe_tokenizer_editor::{
+    AffixScheme, BPETokenizerEditor, HistoryLog, MergeConflictStrategy, TrainConfig,
+};
 
 fn main() -> Result<()> {
     let args = Args::parse();
@@ -26,18 +28,29 @@ fn main() -> Result<()> {
             tokens,
             keep_size,
             whitelist,
+            save_history,
+            max_vocab,
         } => {
-            cmd_add(&input, &output, &tokens, keep_size, whitelist)?;
+            cmd_add(
+                &input,
+                &output,
+                &tokens,
+                keep_size,
+                whitelist,
+                save_history,
+                max_vocab,
+            )?;
         }
         Commands::Remove {
             input,
             output,
             tokens,
+            save_history,
         } => {
-            cmd_remove(&input, &output, &tokens)?;
+            cmd_remove(&input, &output, &tokens, save_history)?;
         }
-        Commands::Stats { input } => {
-            cmd_stats(&input)?;
+        Commands::Stats { input, corpus } => {
+            cmd_stats(&input, corpus)?;
         }
         Commands::Shrink {
             input,
@@ -45,9 +58,31 @@ fn main() -> Result<()> {
             count,
             min_id,
             dry_run,
-            save_removed: _,
+            save_removed,
+            require_coverage,
+            corpus,
         } => {
-            cmd_shrink(&input, &output, count, min_id, dry_run)?;
+            cmd_shrink(
+                &input,
+                &output,
+                count,
+                min_id,
+                dry_run,
+                save_removed,
+                require_coverage,
+                corpus,
+            )?;
+        }
+        Commands::Coverage { input, corpus } => {
+            cmd_coverage(&input, &corpus)?;
+        }
+        Commands::Merge {
+            source,
+            target,
+            output,
+            prefer,
+        } => {
+            cmd_merge(&source, &target, &output, prefer)?;
         }
         Commands::SyncChars {
             source,
@@ -55,9 +90,43 @@ fn main() -> Result<()> {
             output,
             min_id,
             dry_run,
-            save_report: _,
+            save_report,
+            corpus,
+            source_scheme,
+            target_scheme,
+            max_vocab,
         } => {
-            cmd_sync_chars(&source, &target, &output, min_id, dry_run)?;
+            cmd_sync_chars(
+                &source,
+                &target,
+                &output,
+                min_id,
+                dry_run,
+                save_report,
+                corpus,
+                source_scheme,
+                target_scheme,
+                max_vocab,
+            )?;
+        }
+        Commands::Assign {
+            input,
+            output,
+            old,
+            new,
+            mapping,
+        } => {
+            cmd_assign(&input, &output, old, new, mapping)?;
+        }
+        Commands::Train {
+            input,
+            output,
+            corpus,
+            vocab_size,
+            num_merges,
+            min_frequency,
+        } => {
+            cmd_train(&input, &output, &corpus, vocab_size, num_merges, min_frequency)?;
         }
         Commands::SyncShortTokens {
             source,
@@ -67,9 +136,26 @@ fn main() -> Result<()> {
             max_len,
             min_id,
             dry_run,
-            save_report: _,
+            save_report,
+            corpus,
+            source_scheme,
+            target_scheme,
+            max_vocab,
         } => {
-            cmd_sync_short_tokens(&source, &target, &output, min_len, max_len, min_id, dry_run)?;
+            cmd_sync_short_tokens(
+                &source,
+                &target,
+                &output,
+                min_len,
+                max_len,
+                min_id,
+                dry_run,
+                save_report,
+                corpus,
+                source_scheme,
+                target_scheme,
+                max_vocab,
+            )?;
         }
         Commands::Reindex {
             input,
@@ -78,11 +164,128 @@ fn main() -> Result<()> {
         } => {
             cmd_reindex(&input, &output, dry_run)?;
         }
+        Commands::Undo {
+            input,
+            output,
+            history,
+            steps,
+        } => {
+            cmd_undo(&input, &output, &history, steps)?;
+        }
+        Commands::Redo {
+            input,
+            output,
+            history,
+            steps,
+        } => {
+            cmd_redo(&input, &output, &history, steps)?;
+        }
+        Commands::Diff { a, b, output } => {
+            cmd_diff(&a, &b, output)?;
+        }
     }
 
     Ok(())
 }
 
+fn read_history_log(path: &PathBuf) -> Result<HistoryLog> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read history log: {:?}", path))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse history log: {:?}", path))
+}
+
+fn write_history_log(history: &HistoryLog, path: &PathBuf) -> Result<()> {
+    let content = serde_json::to_string_pretty(history)
+        .with_context(|| "Failed to serialize history log")?;
+    fs::write(path, content).with_context(|| format!("Failed to write history log: {:?}", path))?;
+    Ok(())
+}
+
+fn cmd_assign(
+    input: &PathBuf,
+    output: &PathBuf,
+    old: Option<String>,
+    new: Option<String>,
+    mapping_file: Option<PathBuf>,
+) -> Result<()> {
+    println!("Loading tokenizer from: {:?}", input);
+    let mut editor = BPETokenizerEditor::load(input)?;
+
+    let mut renames: Vec<(String, String)> = vec![];
+    if let (Some(old), Some(new)) = (old, new) {
+        renames.push((old, new));
+    }
+    if let Some(mapping_file) = mapping_file {
+        let content = fs::read_to_string(&mapping_file)
+            .with_context(|| format!("Failed to read mapping file: {:?}", mapping_file))?;
+        let mapping: std::collections::HashMap<String, String> = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse mapping JSON: {:?}", mapping_file))?;
+        renames.extend(mapping);
+    }
+
+    if renames.is_empty() {
+        anyhow::bail!("Nothing to assign: pass --old/--new or --mapping");
+    }
+
+    println!("Applying {} rename(s)...", renames.len());
+    let results = editor.reassign_tokens(&renames)?;
+    for result in &results {
+        println!(
+            "  '{}' -> '{}' (id {}, {} merges touched)",
+            result.old_token,
+            result.new_token,
+            result.id,
+            result.merges_touched.len()
+        );
+    }
+
+    editor.save(output)?;
+    println!("\nSaved to: {:?}", output);
+    println!("Final vocab size: {}", editor.vocab_size());
+    println!("Final merges: {}", editor.merges_count());
+
+    Ok(())
+}
+
+fn cmd_train(
+    input: &PathBuf,
+    output: &PathBuf,
+    corpus: &PathBuf,
+    vocab_size: usize,
+    num_merges: Option<usize>,
+    min_frequency: u32,
+) -> Result<()> {
+    println!("Loading tokenizer from: {:?}", input);
+    let mut editor = BPETokenizerEditor::load(input)?;
+
+    println!("Reading corpus from: {:?}", corpus);
+    let content = fs::read_to_string(corpus)
+        .with_context(|| format!("Failed to read corpus file: {:?}", corpus))?;
+    let lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+
+    println!(
+        "Training merges (target vocab_size={}, num_merges={:?}, min_frequency={})...",
+        vocab_size, num_merges, min_frequency
+    );
+    let config = TrainConfig {
+        vocab_size,
+        min_frequency,
+        max_merges: num_merges,
+        ..Default::default()
+    };
+    let result = editor.train_from_corpus(&lines, config);
+
+    editor.save(output)?;
+    println!("\nSaved to: {:?}", output);
+    println!("Initial vocab size: {}", result.initial_vocab_size);
+    println!("Final vocab size: {}", result.final_vocab_size);
+    println!("Merges added: {}", result.merges_added);
+    println!("Tokens added: {}", result.tokens_added);
+
+    Ok(())
+}
+
 fn load_tokens_from_json(path: &PathBuf) -> Result<Vec<String>> {
     let content = fs::read_to_string(path)
         .with_context(|| format!("Failed to read tokens file: {:?}", path))?;
@@ -122,13 +325,46 @@ fn cmd_validate(input: &PathBuf, fix: bool, output: Option<PathBuf>) -> Result<(
             println!("\nRemoving invalid merges...");
             let removed = editor.remove_invalid_merges();
             println!("Removed {} invalid merges", removed);
+        }
+    }
 
-            let out_path = output.unwrap_or_else(|| input.clone());
-            editor.save(&out_path)?;
-            println!("Saved fixed tokenizer to: {:?}", out_path);
+    let order_violations = editor.validate_merge_order();
+    if order_violations.is_empty() {
+        println!("\n✓ Merge order is topologically valid");
+    } else {
+        println!(
+            "\n✗ Found {} merge order violation(s):",
+            order_violations.len()
+        );
+        for (i, v) in order_violations.iter().take(20).enumerate() {
+            println!(
+                "  {}. Merge[{}]: '{}' + '{}' depends on Merge[{}]: '{}' + '{}', which comes later",
+                i + 1,
+                v.merge_index,
+                v.merge.0,
+                v.merge.1,
+                v.depends_on_index,
+                v.depends_on_merge.0,
+                v.depends_on_merge.1
+            );
+        }
+        if order_violations.len() > 20 {
+            println!("  ... and {} more", order_violations.len() - 20);
+        }
+
+        if fix {
+            println!("\nReordering merges topologically...");
+            editor.topological_sort_merges()?;
+            println!("Merges reordered");
         }
     }
 
+    if fix && (!invalid.is_empty() || !order_violations.is_empty()) {
+        let out_path = output.unwrap_or_else(|| input.clone());
+        editor.save(&out_path)?;
+        println!("\nSaved fixed tokenizer to: {:?}", out_path);
+    }
+
     Ok(())
 }
 
@@ -138,6 +374,8 @@ fn cmd_add(
     tokens_file: &PathBuf,
     keep_size: bool,
     whitelist_file: Option<PathBuf>,
+    save_history: Option<PathBuf>,
+    max_vocab: Option<usize>,
 ) -> Result<()> {
     println!("Loading tokenizer from: {:?}", input);
     let mut editor = BPETokenizerEditor::load(input)?;
@@ -183,8 +421,9 @@ fn cmd_add(
             );
         }
     } else {
+        editor.set_max_vocab_size(max_vocab);
         for token in &tokens {
-            let result = editor.add_token_with_merges(token);
+            let result = editor.add_token_with_merges_budgeted(token, true)?;
             if result.added {
                 println!(
                     "  + '{}' via {} ({} merges added)",
@@ -198,15 +437,29 @@ fn cmd_add(
         }
     }
 
+    if !keep_size {
+        check_vocab_budget(&editor, max_vocab)?;
+    }
+
     editor.save(output)?;
     println!("\nSaved to: {:?}", output);
     println!("Final vocab size: {}", editor.vocab_size());
     println!("Final merges: {}", editor.merges_count());
 
+    if let Some(history_path) = save_history {
+        write_history_log(&editor.export_history(), &history_path)?;
+        println!("Saved history log to: {:?}", history_path);
+    }
+
     Ok(())
 }
 
-fn cmd_remove(input: &PathBuf, output: &PathBuf, tokens_file: &PathBuf) -> Result<()> {
+fn cmd_remove(
+    input: &PathBuf,
+    output: &PathBuf,
+    tokens_file: &PathBuf,
+    save_history: Option<PathBuf>,
+) -> Result<()> {
     println!("Loading tokenizer from: {:?}", input);
     let mut editor = BPETokenizerEditor::load(input)?;
 
@@ -235,10 +488,85 @@ fn cmd_remove(input: &PathBuf, output: &PathBuf, tokens_file: &PathBuf) -> Resul
     println!("Final vocab size: {}", editor.vocab_size());
     println!("Final merges: {}", editor.merges_count());
 
+    if let Some(history_path) = save_history {
+        write_history_log(&editor.export_history(), &history_path)?;
+        println!("Saved history log to: {:?}", history_path);
+    }
+
     Ok(())
 }
 
-fn cmd_stats(input: &PathBuf) -> Result<()> {
+/// Undo the most recent `steps` revisions from a saved history log, replay
+/// them against `input`, save the result to `output`, and write the
+/// remaining log back to `history_path` so a later `Undo`/`Redo` can
+/// continue from here.
+fn cmd_undo(input: &PathBuf, output: &PathBuf, history_path: &PathBuf, steps: usize) -> Result<()> {
+    println!("Loading tokenizer from: {:?}", input);
+    let mut editor = BPETokenizerEditor::load(input)?;
+
+    println!("Loading history log from: {:?}", history_path);
+    editor.load_history(read_history_log(history_path)?);
+
+    let mut undone = vec![];
+    for _ in 0..steps {
+        match editor.undo() {
+            Ok(label) => undone.push(label),
+            Err(_) => break,
+        }
+    }
+
+    println!("\n=== Undo Results ===");
+    println!("Requested: {}", steps);
+    println!("Applied: {}", undone.len());
+    for label in &undone {
+        println!("  - {}", label);
+    }
+
+    editor.save(output)?;
+    write_history_log(&editor.export_history(), history_path)?;
+    println!("\nSaved to: {:?}", output);
+    println!("Updated history log: {:?}", history_path);
+    println!("Final vocab size: {}", editor.vocab_size());
+    println!("Final merges: {}", editor.merges_count());
+
+    Ok(())
+}
+
+/// Redo the most recently undone `steps` revisions from a saved history
+/// log, mirroring `cmd_undo`.
+fn cmd_redo(input: &PathBuf, output: &PathBuf, history_path: &PathBuf, steps: usize) -> Result<()> {
+    println!("Loading tokenizer from: {:?}", input);
+    let mut editor = BPETokenizerEditor::load(input)?;
+
+    println!("Loading history log from: {:?}", history_path);
+    editor.load_history(read_history_log(history_path)?);
+
+    let mut redone = vec![];
+    for _ in 0..steps {
+        match editor.redo() {
+            Ok(label) => redone.push(label),
+            Err(_) => break,
+        }
+    }
+
+    println!("\n=== Redo Results ===");
+    println!("Requested: {}", steps);
+    println!("Applied: {}", redone.len());
+    for label in &redone {
+        println!("  - {}", label);
+    }
+
+    editor.save(output)?;
+    write_history_log(&editor.export_history(), history_path)?;
+    println!("\nSaved to: {:?}", output);
+    println!("Updated history log: {:?}", history_path);
+    println!("Final vocab size: {}", editor.vocab_size());
+    println!("Final merges: {}", editor.merges_count());
+
+    Ok(())
+}
+
+fn cmd_stats(input: &PathBuf, corpus: Option<PathBuf>) -> Result<()> {
     println!("Loading tokenizer from: {:?}", input);
     let editor = BPETokenizerEditor::load(input)?;
 
@@ -246,10 +574,46 @@ fn cmd_stats(input: &PathBuf) -> Result<()> {
     println!("Vocab size: {}", editor.vocab_size());
     println!("Merge count: {}", editor.merges_count());
 
-    // Token length distribution
+    // Model config
+    let model = &editor.tokenizer.model;
+    println!("\nModel config:");
+    println!("  unk_token: {}", model.unk_token);
+    println!(
+        "  continuing_subword_prefix: {}",
+        model.continuing_subword_prefix.as_deref().unwrap_or("-")
+    );
+    println!(
+        "  end_of_word_suffix: {}",
+        model.end_of_word_suffix.as_deref().unwrap_or("-")
+    );
+    println!("  byte_fallback: {}", model.byte_fallback);
+    println!("  fuse_unk: {}", model.fuse_unk);
+    println!(
+        "  dropout: {}",
+        model
+            .dropout
+            .map(|d| d.to_string())
+            .unwrap_or_else(|| "-".to_string())
+    );
+
+    // Token length distribution (affix-stripped, so prefix/suffix markers
+    // don't skew the histogram)
+    let prefix = model.continuing_subword_prefix.as_deref();
+    let suffix = model.end_of_word_suffix.as_deref();
     let mut len_dist: std::collections::HashMap<usize, usize> = std::collections::HashMap::new();
     for tok in editor.tokenizer.model.vocab.keys() {
-        let count = tok.chars().count();
+        let mut stripped: &str = tok;
+        if let Some(p) = prefix {
+            if !p.is_empty() {
+                stripped = stripped.strip_prefix(p).unwrap_or(stripped);
+            }
+        }
+        if let Some(s) = suffix {
+            if !s.is_empty() {
+                stripped = stripped.strip_suffix(s).unwrap_or(stripped);
+            }
+        }
+        let count = stripped.chars().count();
         *len_dist.entry(count).or_default() += 1;
     }
 
@@ -286,6 +650,167 @@ fn cmd_stats(input: &PathBuf) -> Result<()> {
         println!("\n✗ {} invalid merges found", invalid.len());
     }
 
+    if let Some(corpus) = corpus {
+        println!("\nChecking corpus encode coverage against: {:?}", corpus);
+        let lines = read_corpus_lines(&corpus)?;
+        let coverage = editor.encode_coverage(&lines);
+        println!(
+            "Encode coverage: {:.2}% ({}/{} tokens not unk)",
+            coverage.covered_pct,
+            coverage.tokens_checked - coverage.unk_tokens,
+            coverage.tokens_checked
+        );
+    }
+
+    Ok(())
+}
+
+fn cmd_coverage(input: &PathBuf, corpus: &PathBuf) -> Result<()> {
+    println!("Loading tokenizer from: {:?}", input);
+    let editor = BPETokenizerEditor::load(input)?;
+
+    println!("Reading corpus from: {:?}", corpus);
+    let content = fs::read_to_string(corpus)
+        .with_context(|| format!("Failed to read corpus file: {:?}", corpus))?;
+    let lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+
+    let result = editor.check_coverage(lines.into_iter());
+
+    println!("\n=== Coverage Results ===");
+    println!("Characters checked: {}", result.chars_checked);
+    println!(
+        "Covered via byte_fallback: {}",
+        result.byte_fallback_chars.len()
+    );
+    println!("Missing (unencodable): {}", result.missing_chars.len());
+    if !result.missing_chars.is_empty() {
+        println!("  {:?}", result.missing_chars);
+    }
+    if result.fully_covered {
+        println!("\n✓ Corpus is fully encodable");
+    } else {
+        println!("\n✗ Corpus has uncovered characters");
+    }
+
+    Ok(())
+}
+
+/// Refuse to save if `editor`'s current vocab size exceeds `max_vocab`,
+/// reporting how many tokens over budget it is. A no-op if `max_vocab` is
+/// `None`.
+fn check_vocab_budget(editor: &BPETokenizerEditor, max_vocab: Option<usize>) -> Result<()> {
+    if let Some(max) = max_vocab {
+        let size = editor.vocab_size();
+        if size > max {
+            anyhow::bail!(
+                "Refusing to save: vocab size {} exceeds --max-vocab {} ({} token(s) over budget)",
+                size,
+                max,
+                size - max
+            );
+        }
+    }
+    Ok(())
+}
+
+fn check_coverage_or_bail(editor: &BPETokenizerEditor, corpus: &PathBuf) -> Result<()> {
+    let content = fs::read_to_string(corpus)
+        .with_context(|| format!("Failed to read coverage corpus: {:?}", corpus))?;
+    let lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
+    let result = editor.check_coverage(lines.into_iter());
+    if !result.fully_covered {
+        anyhow::bail!(
+            "Refusing to save: {} character(s) became unencodable: {:?}",
+            result.missing_chars.len(),
+            result.missing_chars
+        );
+    }
+    Ok(())
+}
+
+fn read_corpus_lines(corpus: &PathBuf) -> Result<Vec<String>> {
+    let content = fs::read_to_string(corpus)
+        .with_context(|| format!("Failed to read corpus file: {:?}", corpus))?;
+    Ok(content.lines().map(|l| l.to_string()).collect())
+}
+
+/// Populate `editor.usage_counts` from real corpus encodes so
+/// `find_tokens_to_shrink` ranks victims by actual usage instead of the
+/// plain length/ID heuristic, reporting the corpus's total token count
+/// beforehand so callers can compare it against the post-shrink count.
+fn apply_corpus_usage(editor: &mut BPETokenizerEditor, corpus: &PathBuf) -> Result<u64> {
+    println!("Reading corpus from: {:?}", corpus);
+    let lines = read_corpus_lines(corpus)?;
+    let (counts, total_tokens) = editor.corpus_usage_counts(&lines);
+    println!("Corpus token count before: {}", total_tokens);
+    editor.set_usage_counts(counts);
+    Ok(total_tokens)
+}
+
+/// Resolve a `--source-scheme`/`--target-scheme` CLI override against the
+/// tokenizer's auto-detected convention (`SchemeOverride::Auto` keeps it).
+fn resolve_scheme(scheme: SchemeOverride, auto: AffixScheme) -> AffixScheme {
+    match scheme {
+        SchemeOverride::Auto => auto,
+        SchemeOverride::Wordpiece => AffixScheme {
+            continuing_subword_prefix: Some("##".to_string()),
+            end_of_word_suffix: None,
+            byte_level: false,
+        },
+        SchemeOverride::ByteLevel => AffixScheme {
+            continuing_subword_prefix: None,
+            end_of_word_suffix: None,
+            byte_level: true,
+        },
+        SchemeOverride::Plain => AffixScheme {
+            continuing_subword_prefix: None,
+            end_of_word_suffix: None,
+            byte_level: false,
+        },
+    }
+}
+
+fn cmd_merge(
+    source: &PathBuf,
+    target: &PathBuf,
+    output: &PathBuf,
+    prefer: MergePreference,
+) -> Result<()> {
+    println!("Loading source tokenizer from: {:?}", source);
+    let source_editor = BPETokenizerEditor::load(source)?;
+
+    println!("Loading target tokenizer from: {:?}", target);
+    let mut target_editor = BPETokenizerEditor::load(target)?;
+
+    let strategy = match prefer {
+        MergePreference::Source => MergeConflictStrategy::PreferSource,
+        MergePreference::Target => MergeConflictStrategy::PreferTarget,
+    };
+
+    println!("\nMerging source into target...");
+    let result = target_editor.merge_with(&source_editor, strategy);
+
+    println!("\n=== Merge Results ===");
+    println!("Source vocab: {}", result.source_vocab_size);
+    println!("Target vocab: {}", result.target_vocab_size);
+    println!(
+        "Tokens added from source: {}",
+        result.tokens_added_from_source
+    );
+    println!("ID conflicts: {}", result.id_conflicts);
+    println!(
+        "Merges added from source: {}",
+        result.merges_added_from_source
+    );
+    println!("Merges dropped: {}", result.merges_dropped);
+    println!("Merges repaired (dropped in final validation sweep): {}", result.merges_repaired);
+    println!("IDs remapped during reindex: {}", result.ids_remapped);
+    println!("Final vocab: {}", result.final_vocab_size);
+    println!("Final merges: {}", result.final_merges_count);
+
+    target_editor.save(output)?;
+    println!("\nSaved to: {:?}", output);
+
     Ok(())
 }
 
@@ -295,12 +820,21 @@ fn cmd_shrink(
     count: usize,
     min_id: u32,
     dry_run: bool,
+    save_removed: Option<PathBuf>,
+    require_coverage: Option<PathBuf>,
+    corpus: Option<PathBuf>,
 ) -> Result<()> {
     println!("Loading tokenizer from: {:?}", input);
     let mut editor = BPETokenizerEditor::load(input)?;
 
     println!("Current vocab size: {}", editor.vocab_size());
     println!("Current merge count: {}", editor.merges_count());
+
+    let corpus_before_tokens = corpus
+        .as_ref()
+        .map(|c| apply_corpus_usage(&mut editor, c))
+        .transpose()?;
+
     println!(
         "\nFinding {} longest tokens with ID >= {}...",
         count, min_id
@@ -334,9 +868,29 @@ fn cmd_shrink(
     println!("Final vocab: {}", result.final_vocab_size);
     println!("Final merges: {}", result.final_merges_count);
 
+    if let Some(corpus) = require_coverage {
+        println!("\nChecking coverage against: {:?}", corpus);
+        check_coverage_or_bail(&editor, &corpus)?;
+        println!("✓ Coverage preserved");
+    }
+
+    if let (Some(corpus), Some(before)) = (&corpus, corpus_before_tokens) {
+        let lines = read_corpus_lines(corpus)?;
+        let (_, after) = editor.corpus_usage_counts(&lines);
+        println!(
+            "\nCorpus token count after: {} (before: {})",
+            after, before
+        );
+    }
+
     editor.save(output)?;
     println!("\nSaved to: {:?}", output);
 
+    if let Some(history_path) = save_removed {
+        write_history_log(&editor.export_history(), &history_path)?;
+        println!("Saved history log to: {:?}", history_path);
+    }
+
     Ok(())
 }
 
@@ -346,6 +900,11 @@ fn cmd_sync_chars(
     output: &PathBuf,
     min_id: u32,
     dry_run: bool,
+    save_report: Option<PathBuf>,
+    corpus: Option<PathBuf>,
+    source_scheme: SchemeOverride,
+    target_scheme: SchemeOverride,
+    max_vocab: Option<usize>,
 ) -> Result<()> {
     println!("Loading source tokenizer from: {:?}", source);
     let source_editor = BPETokenizerEditor::load(source)?;
@@ -353,6 +912,9 @@ fn cmd_sync_chars(
     println!("Loading target tokenizer from: {:?}", target);
     let mut target_editor = BPETokenizerEditor::load(target)?;
 
+    let source_scheme = resolve_scheme(source_scheme, source_editor.affix_scheme());
+    let target_scheme = resolve_scheme(target_scheme, target_editor.affix_scheme());
+
     let source_chars = source_editor.get_single_char_tokens();
     let target_chars = target_editor.get_single_char_tokens();
 
@@ -403,7 +965,13 @@ fn cmd_sync_chars(
         min_id
     );
 
-    let result = target_editor.sync_single_chars(&source_chars, min_id);
+    let corpus_before_tokens = corpus
+        .as_ref()
+        .map(|c| apply_corpus_usage(&mut target_editor, c))
+        .transpose()?;
+
+    let result =
+        target_editor.sync_single_chars(&source_chars, min_id, &source_scheme, &target_scheme);
 
     println!("\n=== Sync Results ===");
     println!("Initial vocab: {}", result.initial_vocab_size);
@@ -411,6 +979,7 @@ fn cmd_sync_chars(
     println!("Chars in source: {}", result.chars_in_source);
     println!("Already present: {}", result.chars_already_present);
     println!("Chars added: {}", result.chars_added.len());
+    println!("Chars skipped (no faithful translation): {}", result.chars_skipped.len());
     println!("Tokens removed: {}", result.tokens_removed.len());
     println!(
         "Total cascade tokens removed: {}",
@@ -427,9 +996,36 @@ fn cmd_sync_chars(
         }
     }
 
+    if !result.chars_skipped.is_empty() && result.chars_skipped.len() <= 20 {
+        println!("\nChars skipped: {:?}", result.chars_skipped);
+    }
+
+    if let (Some(corpus), Some(before)) = (&corpus, corpus_before_tokens) {
+        let lines = read_corpus_lines(corpus)?;
+        let (_, after) = target_editor.corpus_usage_counts(&lines);
+        println!(
+            "\nCorpus token count after: {} (before: {})",
+            after, before
+        );
+        let coverage = target_editor.encode_coverage(&lines);
+        println!(
+            "Encode coverage after: {:.2}% ({}/{} tokens not unk)",
+            coverage.covered_pct,
+            coverage.tokens_checked - coverage.unk_tokens,
+            coverage.tokens_checked
+        );
+    }
+
+    check_vocab_budget(&target_editor, max_vocab)?;
+
     target_editor.save(output)?;
     println!("\nSaved to: {:?}", output);
 
+    if let Some(history_path) = save_report {
+        write_history_log(&target_editor.export_history(), &history_path)?;
+        println!("Saved history log to: {:?}", history_path);
+    }
+
     Ok(())
 }
 
@@ -441,6 +1037,11 @@ fn cmd_sync_short_tokens(
     max_len: usize,
     min_id: u32,
     dry_run: bool,
+    save_report: Option<PathBuf>,
+    corpus: Option<PathBuf>,
+    source_scheme: SchemeOverride,
+    target_scheme: SchemeOverride,
+    max_vocab: Option<usize>,
 ) -> Result<()> {
     println!("Loading source tokenizer from: {:?}", source);
     let source_editor = BPETokenizerEditor::load(source)?;
@@ -448,6 +1049,9 @@ fn cmd_sync_short_tokens(
     println!("Loading target tokenizer from: {:?}", target);
     let mut target_editor = BPETokenizerEditor::load(target)?;
 
+    let source_scheme = resolve_scheme(source_scheme, source_editor.affix_scheme());
+    let target_scheme = resolve_scheme(target_scheme, target_editor.affix_scheme());
+
     let source_tokens = source_editor.get_tokens_by_length(min_len, max_len);
     let source_merges: Vec<(String, String)> = source_editor
         .tokenizer
@@ -509,7 +1113,18 @@ fn cmd_sync_short_tokens(
         min_id
     );
 
-    let result = target_editor.sync_short_tokens(&source_tokens, &source_merges, min_id);
+    let corpus_before_tokens = corpus
+        .as_ref()
+        .map(|c| apply_corpus_usage(&mut target_editor, c))
+        .transpose()?;
+
+    let result = target_editor.sync_short_tokens(
+        &source_tokens,
+        &source_merges,
+        min_id,
+        &source_scheme,
+        &target_scheme,
+    );
 
     println!("\n=== Sync Results ===");
     println!("Initial vocab: {}", result.initial_vocab_size);
@@ -519,6 +1134,10 @@ fn cmd_sync_short_tokens(
     println!("Tokens added: {}", result.tokens_added.len());
     println!("Merges added: {}", result.merges_added);
     println!("Merges already present: {}", result.merges_already_present);
+    println!(
+        "Tokens skipped (no faithful translation): {}",
+        result.tokens_skipped.len()
+    );
     println!("Tokens removed: {}", result.tokens_removed.len());
     println!(
         "Total cascade tokens removed: {}",
@@ -538,9 +1157,90 @@ fn cmd_sync_short_tokens(
         }
     }
 
+    if !result.tokens_skipped.is_empty() && result.tokens_skipped.len() <= 20 {
+        println!("\nTokens skipped: {:?}", result.tokens_skipped);
+    }
+
+    if let (Some(corpus), Some(before)) = (&corpus, corpus_before_tokens) {
+        let lines = read_corpus_lines(corpus)?;
+        let (_, after) = target_editor.corpus_usage_counts(&lines);
+        println!(
+            "\nCorpus token count after: {} (before: {})",
+            after, before
+        );
+        let coverage = target_editor.encode_coverage(&lines);
+        println!(
+            "Encode coverage after: {:.2}% ({}/{} tokens not unk)",
+            coverage.covered_pct,
+            coverage.tokens_checked - coverage.unk_tokens,
+            coverage.tokens_checked
+        );
+    }
+
+    check_vocab_budget(&target_editor, max_vocab)?;
+
     target_editor.save(output)?;
     println!("\nSaved to: {:?}", output);
 
+    if let Some(history_path) = save_report {
+        write_history_log(&target_editor.export_history(), &history_path)?;
+        println!("Saved history log to: {:?}", history_path);
+    }
+
+    Ok(())
+}
+
+fn cmd_diff(a: &PathBuf, b: &PathBuf, output: Option<PathBuf>) -> Result<()> {
+    println!("Loading tokenizer A from: {:?}", a);
+    let editor_a = BPETokenizerEditor::load(a)?;
+
+    println!("Loading tokenizer B from: {:?}", b);
+    let editor_b = BPETokenizerEditor::load(b)?;
+
+    let result = editor_a.diff_with(&editor_b);
+
+    println!("\n=== Diff Results ===");
+    println!("A vocab: {}, B vocab: {}", result.a_vocab_size, result.b_vocab_size);
+    println!(
+        "A merges: {}, B merges: {}",
+        result.a_merges_count, result.b_merges_count
+    );
+    println!(
+        "Buckets changed: {} / {}",
+        result.buckets_changed, result.buckets_total
+    );
+
+    for bucket in result.bucket_diffs.iter().take(20) {
+        println!(
+            "  [{}-{}] +{} tokens, -{} tokens, +{} merges, -{} merges",
+            bucket.id_range_start,
+            bucket.id_range_end,
+            bucket.tokens_added.len(),
+            bucket.tokens_removed.len(),
+            bucket.merges_added.len(),
+            bucket.merges_removed.len()
+        );
+    }
+    if result.bucket_diffs.len() > 20 {
+        println!("  ... and {} more changed buckets", result.bucket_diffs.len() - 20);
+    }
+
+    println!("\nTokens re-assigned a different ID in B: {}", result.id_remap.len());
+    for remap in result.id_remap.iter().take(20) {
+        println!("  '{}': {} -> {}", remap.token, remap.a_id, remap.b_id);
+    }
+    if result.id_remap.len() > 20 {
+        println!("  ... and {} more", result.id_remap.len() - 20);
+    }
+
+    if let Some(output) = output {
+        let content = serde_json::to_string_pretty(&result)
+            .with_context(|| "Failed to serialize diff report")?;
+        fs::write(&output, content)
+            .with_context(|| format!("Failed to write diff report: {:?}", output))?;
+        println!("\nSaved full diff report to: {:?}", output);
+    }
+
     Ok(())
 }
 
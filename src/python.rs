@@ -53,6 +53,34 @@ pub struct PyRemovalResult {
     pub removed_merges: Vec<(String, String)>,
 }
 
+/// Result of learning new merges from a corpus via `train_merges`
+#[pyclass(name = "TrainResult")]
+#[derive(Clone)]
+pub struct PyTrainResult {
+    #[pyo3(get)]
+    pub initial_vocab_size: usize,
+    #[pyo3(get)]
+    pub final_vocab_size: usize,
+    #[pyo3(get)]
+    pub merges_learned: usize,
+    #[pyo3(get)]
+    pub tokens_added: usize,
+}
+
+/// Result of renaming a token in place via `assign_token`
+#[pyclass(name = "AssignResult")]
+#[derive(Clone)]
+pub struct PyAssignResult {
+    #[pyo3(get)]
+    pub old_token: String,
+    #[pyo3(get)]
+    pub new_token: String,
+    #[pyo3(get)]
+    pub id: u32,
+    #[pyo3(get)]
+    pub merges_rewritten: usize,
+}
+
 /// Result of shrink operation
 #[pyclass(name = "ShrinkResult")]
 #[derive(Clone)]
@@ -171,6 +199,58 @@ impl PyBPETokenizerEditor {
             .map_err(|e| PyIOError::new_err(format!("Failed to save tokenizer: {}", e)))
     }
 
+    /// Continuation prefix marking mid-word subwords (e.g. `##`), if any
+    #[getter]
+    fn continuing_subword_prefix(&self) -> Option<String> {
+        self.inner.tokenizer.model.continuing_subword_prefix.clone()
+    }
+
+    /// Suffix marking the end of a word (e.g. `</w>`), if any
+    #[getter]
+    fn end_of_word_suffix(&self) -> Option<String> {
+        self.inner.tokenizer.model.end_of_word_suffix.clone()
+    }
+
+    /// Set (or clear, passing None) the unknown-token string emitted by
+    /// `encode` for symbols missing from vocab.
+    #[pyo3(signature = (token))]
+    fn set_unk_token(&mut self, token: Option<&str>) {
+        self.inner.set_unk_token(token);
+    }
+
+    /// Set whether consecutive unk emissions collapse into a single unk
+    /// token during `encode`.
+    #[pyo3(signature = (fuse_unk))]
+    fn set_fuse_unk(&mut self, fuse_unk: bool) {
+        self.inner.set_fuse_unk(fuse_unk);
+    }
+
+    /// Build a frequency-ranked out-of-vocabulary report over a corpus.
+    ///
+    /// Args:
+    ///     texts: Corpus lines to check coverage against
+    ///
+    /// Returns:
+    ///     Dict with total_chars, covered_chars, unk_count, and top_oov (a
+    ///     list of (char, count) tuples, most frequent first)
+    #[pyo3(signature = (texts))]
+    fn coverage_report(&self, texts: Vec<String>) -> PyResult<Py<PyDict>> {
+        let report = self.inner.coverage_report(&texts);
+        Python::with_gil(|py| {
+            let dict = PyDict::new(py);
+            dict.set_item("total_chars", report.total_chars)?;
+            dict.set_item("covered_chars", report.covered_chars)?;
+            dict.set_item("unk_count", report.unk_count)?;
+            let top_oov: Vec<(String, u64)> = report
+                .top_oov
+                .into_iter()
+                .map(|(ch, count)| (ch.to_string(), count))
+                .collect();
+            dict.set_item("top_oov", top_oov)?;
+            Ok(dict.into())
+        })
+    }
+
     /// Export tokenizer to JSON string
     ///
     /// Returns:
@@ -441,6 +521,133 @@ impl PyBPETokenizerEditor {
             .collect()
     }
 
+    /// Rename a token's content in place, keeping its numeric ID and
+    /// rewriting every merge that uses it as a left or right input. Merges
+    /// that produce `old` keep their inputs unchanged.
+    ///
+    /// Args:
+    ///     old: Existing token content
+    ///     new: New content to assign to that ID
+    ///
+    /// Returns:
+    ///     AssignResult with the ID and `merges_rewritten`, the count of
+    ///     merges that use `old` as an input or produce it
+    ///
+    /// Raises:
+    ///     ValueError: If `old` is missing or `new` already exists at a
+    ///         different ID
+    #[pyo3(signature = (old, new))]
+    fn assign_token(&mut self, old: &str, new: &str) -> PyResult<PyAssignResult> {
+        let result = self
+            .inner
+            .reassign_token(old, new)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(PyAssignResult {
+            old_token: result.old_token,
+            new_token: result.new_token,
+            id: result.id,
+            merges_rewritten: result.merges_touched.len(),
+        })
+    }
+
+    /// Batch version of `assign_token`: zips `old_list` and `new_list` and
+    /// validates every rename before applying any of them.
+    ///
+    /// Args:
+    ///     old_list: Existing token contents
+    ///     new_list: New contents, same length as `old_list`
+    ///
+    /// Returns:
+    ///     List of AssignResult, one per rename, in order
+    ///
+    /// Raises:
+    ///     ValueError: If the lists differ in length, or any rename is
+    ///         invalid
+    #[pyo3(signature = (old_list, new_list))]
+    fn assign_tokens(
+        &mut self,
+        old_list: Vec<String>,
+        new_list: Vec<String>,
+    ) -> PyResult<Vec<PyAssignResult>> {
+        if old_list.len() != new_list.len() {
+            return Err(PyValueError::new_err(
+                "old_list and new_list must be the same length",
+            ));
+        }
+
+        let renames: Vec<(String, String)> = old_list.into_iter().zip(new_list).collect();
+        let results = self
+            .inner
+            .reassign_tokens(&renames)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+
+        Ok(results
+            .into_iter()
+            .map(|result| PyAssignResult {
+                old_token: result.old_token,
+                new_token: result.new_token,
+                id: result.id,
+                merges_rewritten: result.merges_touched.len(),
+            })
+            .collect())
+    }
+
+    /// Tokenize text into IDs using the edited vocab and merges.
+    ///
+    /// Args:
+    ///     text: Input text to tokenize
+    ///     dropout: Optional BPE-dropout rate (0.0-1.0) overriding the
+    ///         model's configured dropout for this call
+    ///
+    /// Returns:
+    ///     List of token IDs
+    #[pyo3(signature = (text, dropout = None))]
+    fn encode(&self, text: &str, dropout: Option<f32>) -> Vec<u32> {
+        self.inner.encode(text, dropout)
+    }
+
+    /// Reconstruct text from token IDs.
+    ///
+    /// Args:
+    ///     ids: Token IDs to decode
+    ///
+    /// Returns:
+    ///     Concatenated token strings
+    #[pyo3(signature = (ids))]
+    fn decode(&self, ids: Vec<u32>) -> String {
+        self.inner.decode(&ids)
+    }
+
+    /// Learn new BPE merges from a corpus, growing the vocabulary toward
+    /// `target_vocab_size`.
+    ///
+    /// Args:
+    ///     texts: Corpus lines to learn merges from
+    ///     target_vocab_size: Stop once the vocab reaches this size
+    ///     min_frequency: Minimum pair frequency required to add a merge
+    ///     limit_alphabet: Optional cap on the base single-char alphabet
+    ///
+    /// Returns:
+    ///     TrainResult with merges learned, tokens added, and final vocab size
+    #[pyo3(signature = (texts, target_vocab_size, min_frequency = 2, limit_alphabet = None))]
+    fn train_merges(
+        &mut self,
+        texts: Vec<String>,
+        target_vocab_size: usize,
+        min_frequency: u32,
+        limit_alphabet: Option<usize>,
+    ) -> PyTrainResult {
+        let result = self
+            .inner
+            .train_merges(&texts, target_vocab_size, min_frequency, limit_alphabet);
+        PyTrainResult {
+            initial_vocab_size: result.initial_vocab_size,
+            final_vocab_size: result.final_vocab_size,
+            merges_learned: result.merges_added,
+            tokens_added: result.tokens_added,
+        }
+    }
+
     /// Shrink vocabulary by removing N longest non-special tokens
     ///
     /// Selection criteria (in order):
@@ -508,7 +715,11 @@ impl PyBPETokenizerEditor {
             .map_err(|e| PyIOError::new_err(format!("Failed to load source tokenizer: {}", e)))?;
 
         let source_chars = source.get_single_char_tokens();
-        let result = self.inner.sync_single_chars(&source_chars, min_id);
+        let source_scheme = source.affix_scheme();
+        let target_scheme = self.inner.affix_scheme();
+        let result = self
+            .inner
+            .sync_single_chars(&source_chars, min_id, &source_scheme, &target_scheme);
 
         Python::with_gil(|py| {
             let dict = PyDict::new(py);
@@ -639,3 +850,63 @@ pub fn bpe_tokenizer_editor(m: &Bound<'_, PyModule>) -> PyResult<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_tokenizer_json() -> &'static str {
+        r#"{
+            "version": "1.0",
+            "truncation": null,
+            "padding": null,
+            "added_tokens": [],
+            "normalizer": null,
+            "pre_tokenizer": null,
+            "post_processor": null,
+            "decoder": null,
+            "model": {
+                "type": "BPE",
+                "dropout": null,
+                "unk_token": "<unk>",
+                "continuing_subword_prefix": null,
+                "end_of_word_suffix": null,
+                "fuse_unk": false,
+                "byte_fallback": false,
+                "ignore_merges": false,
+                "vocab": {"a": 0, "b": 1, "ab": 2},
+                "merges": [["a", "b"]]
+            }
+        }"#
+    }
+
+    /// Smoke test for the PyO3 binding layer: builds an editor via
+    /// `from_json`, mutates it through the exposed methods (including one,
+    /// `get_vocab`, that builds a `PyDict` under the GIL), and round-trips
+    /// it back through `to_json`, so a regression that breaks the Rust<->
+    /// Python boundary itself (not just the underlying editor logic, which
+    /// has its own tests) fails here.
+    #[test]
+    fn test_pyo3_editor_round_trips_through_json_and_gil_bound_methods() {
+        pyo3::prepare_freethreaded_python();
+
+        let mut editor = PyBPETokenizerEditor::from_json(sample_tokenizer_json()).unwrap();
+        assert_eq!(editor.vocab_size(), 3);
+        assert!(editor.has_token("ab"));
+
+        let result = editor.add_token("abc");
+        assert!(result.added);
+        assert!(editor.has_token("abc"));
+
+        let vocab = editor.get_vocab().unwrap();
+        Python::with_gil(|py| {
+            let bound = vocab.bind(py);
+            assert!(bound.get_item("abc").unwrap().is_some());
+        });
+
+        let json = editor.to_json().unwrap();
+        let round_tripped = PyBPETokenizerEditor::from_json(&json).unwrap();
+        assert_eq!(round_tripped.vocab_size(), editor.vocab_size());
+        assert!(round_tripped.has_token("abc"));
+    }
+}
@@ -79,6 +79,9 @@ pub struct SyncCharsResult {
     pub tokens_removed: Vec<TokenRemovalInfo>,
     pub total_tokens_removed: usize,
     pub total_merges_removed: usize,
+    /// Source chars with no faithful translation into the target's
+    /// affixing/byte-level convention (see `translate_token`)
+    pub chars_skipped: Vec<String>,
 }
 
 /// Information about a short token addition
@@ -104,6 +107,9 @@ pub struct SyncShortTokensResult {
     pub tokens_removed: Vec<TokenRemovalInfo>,
     pub total_tokens_removed: usize,
     pub total_merges_removed: usize,
+    /// Source tokens with no faithful translation into the target's
+    /// affixing/byte-level convention (see `translate_token`)
+    pub tokens_skipped: Vec<String>,
 }
 
 /// Information about an ID remapping
@@ -114,6 +120,103 @@ pub struct IdRemapInfo {
     pub new_id: u32,
 }
 
+/// Result of reassigning a token's content while preserving its ID
+#[derive(Debug, Clone, Serialize)]
+pub struct ReassignResult {
+    pub old_token: String,
+    pub new_token: String,
+    pub id: u32,
+    pub merges_touched: Vec<(String, String)>,
+}
+
+/// Result of training new merges from a text corpus
+#[derive(Debug, Clone, Serialize)]
+pub struct TrainResult {
+    pub initial_vocab_size: usize,
+    pub final_vocab_size: usize,
+    pub merges_added: usize,
+    pub tokens_added: usize,
+}
+
+/// Report of ID collisions/gaps found while reassigning sequential IDs
+#[derive(Debug, Clone, Serialize)]
+pub struct IdConsistencyReport {
+    /// IDs claimed by more than one token, with the offending tokens
+    pub collisions: Vec<(u32, Vec<String>)>,
+    /// IDs missing from the (min..=max) range before reassignment
+    pub gaps: Vec<u32>,
+}
+
+/// Result of checking whether a corpus is fully encodable against a
+/// tokenizer's vocab
+#[derive(Debug, Clone, Serialize)]
+pub struct CoverageResult {
+    pub chars_checked: usize,
+    /// Characters covered only via `byte_fallback` byte tokens, not a
+    /// direct char token
+    pub byte_fallback_chars: Vec<char>,
+    /// Characters with no char token and no byte-fallback coverage
+    pub missing_chars: Vec<char>,
+    pub fully_covered: bool,
+}
+
+/// Frequency-ranked out-of-vocabulary report over a corpus, for deciding
+/// which single-char tokens to `sync_single_chars` or merges to
+/// `train_merges` before shipping an edited vocabulary.
+#[derive(Debug, Clone, Serialize)]
+pub struct CoverageReport {
+    pub total_chars: usize,
+    pub covered_chars: usize,
+    /// Total occurrences of out-of-vocabulary characters
+    pub unk_count: usize,
+    /// Out-of-vocabulary characters and their occurrence counts, most
+    /// frequent first
+    pub top_oov: Vec<(char, u64)>,
+}
+
+/// Fraction of a corpus that round-trips through `encode_to_tokens` without
+/// producing a `unk_token`, computed by the real BPE encoder (unlike
+/// `CoverageResult`/`CoverageReport`, which only check character-level
+/// presence in vocab).
+#[derive(Debug, Clone, Serialize)]
+pub struct EncodeCoverageResult {
+    pub tokens_checked: u64,
+    pub unk_tokens: u64,
+    pub covered_pct: f64,
+}
+
+/// Result of unioning another tokenizer's vocab and merges into this one
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeResult {
+    pub source_vocab_size: usize,
+    pub target_vocab_size: usize,
+    pub tokens_added_from_source: usize,
+    /// Tokens present in both vocabs under different numeric IDs
+    pub id_conflicts: usize,
+    pub merges_added_from_source: usize,
+    /// Imported merges dropped because an operand or the result wasn't in
+    /// the unioned vocab
+    pub merges_dropped: usize,
+    /// Merges dropped in the final validation sweep over the whole unioned
+    /// table (target's own merges included), after the union and reindex
+    pub merges_repaired: usize,
+    pub final_vocab_size: usize,
+    pub final_merges_count: usize,
+    pub ids_remapped: usize,
+}
+
+/// A merge that appears before a merge producing one of its own inputs,
+/// which would make it misfire (or fail to fire) under greedy left-to-right
+/// BPE application
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeOrderViolation {
+    pub merge_index: usize,
+    pub merge: (String, String),
+    /// Index of the merge producing an input, that appears later in the list
+    pub depends_on_index: usize,
+    pub depends_on_merge: (String, String),
+}
+
 /// Result of vocabulary reindexing
 #[derive(Debug, Serialize)]
 pub struct ReindexResult {
@@ -126,3 +229,41 @@ pub struct ReindexResult {
     pub ids_remapped: usize,
     pub gaps_removed: usize,
 }
+
+/// Which (token, id) vocab entries and merge pairs were added/removed in a
+/// single token-ID-range bucket between two tokenizers (see `DiffResult`)
+#[derive(Debug, Clone, Serialize)]
+pub struct BucketDiff {
+    pub id_range_start: u32,
+    pub id_range_end: u32,
+    pub tokens_added: Vec<(String, u32)>,
+    pub tokens_removed: Vec<(String, u32)>,
+    pub merges_added: Vec<(String, String)>,
+    pub merges_removed: Vec<(String, String)>,
+}
+
+/// A token present in both tokenizers under a different numeric ID
+#[derive(Debug, Clone, Serialize)]
+pub struct IdRemap {
+    pub token: String,
+    pub a_id: u32,
+    pub b_id: u32,
+}
+
+/// Structural diff between two tokenizers (`a` and `b`), computed by
+/// comparing Merkle bucket roots over the vocab and merge tables so
+/// unchanged ID ranges skip the detailed set comparison on large
+/// vocabularies (every entry is still hashed once to build the roots)
+#[derive(Debug, Clone, Serialize)]
+pub struct DiffResult {
+    pub a_vocab_size: usize,
+    pub b_vocab_size: usize,
+    pub a_merges_count: usize,
+    pub b_merges_count: usize,
+    pub buckets_total: usize,
+    pub buckets_changed: usize,
+    pub bucket_diffs: Vec<BucketDiff>,
+    /// Tokens present in both vocabs under different IDs - the direct
+    /// counterpart to what `reindex_vocab` remaps
+    pub id_remap: Vec<IdRemap>,
+}